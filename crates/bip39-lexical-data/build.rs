@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::env;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
@@ -5,28 +6,136 @@ use std::path::Path;
 
 // https://doc.rust-lang.org/cargo/reference/build-scripts.html#case-study-code-generation
 
-/// Extract words from simple wordlist
-fn words_simple(mut f_dest: &File, const_name: &str, fname_src: &str) {
-    write!(f_dest, "pub const {const_name}: &[&str] = &[").unwrap();
-
-    let f_src = BufReader::new(File::open(fname_src).unwrap());
-    for line in f_src.lines() {
-        match line {
-            Ok(line) => {
-                let word = line.trim();
-                write!(f_dest, "\"{word}\",").unwrap();
-            }
-            Err(_e) => panic!("Unable to read line from internal file"),
+/// Number of words in every BIP39 wordlist. Each word encodes 11 bits.
+const BIP39_NUM_WORDS: usize = 2048;
+
+/// Stop the build, pointing at the offending line of the wordlist data.
+fn fail(fname_src: &str, line_num: usize, msg: &str) -> ! {
+    panic!("{fname_src}:{line_num}: {msg}");
+}
+
+/// Extract words from simple wordlist, and generate a `Wordlist` with metadata about them.
+///
+/// Every line must consist of exactly one word, with no surrounding whitespace.
+fn words_simple(
+    mut f_dest: &File,
+    const_name: &str,
+    fname_src: &str,
+    name: &str,
+    source_url: &str,
+) {
+    let mut words = vec![];
+    let mut seen = HashSet::new();
+    let f_src = BufReader::new(
+        File::open(fname_src).unwrap_or_else(|e| panic!("Unable to open {fname_src}: {e}")),
+    );
+    for (i, line) in f_src.lines().enumerate() {
+        let line_num = i + 1;
+        let word = line
+            .unwrap_or_else(|e| fail(fname_src, line_num, &format!("Unable to read line: {e}")));
+
+        if word.is_empty() {
+            fail(fname_src, line_num, "Empty word");
+        }
+        if word.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            fail(
+                fname_src,
+                line_num,
+                &format!("Word {word:?} contains whitespace or control characters"),
+            );
         }
+        if !seen.insert(word.clone()) {
+            fail(fname_src, line_num, &format!("Duplicate word {word:?}"));
+        }
+        words.push(word);
+    }
+
+    if words.len() != BIP39_NUM_WORDS {
+        fail(
+            fname_src,
+            words.len(),
+            &format!("Expected {BIP39_NUM_WORDS} words, found {}", words.len()),
+        );
     }
 
+    // Words are written using `Debug` formatting, which escapes them as Rust string literals.
+    write!(f_dest, "pub const WL_{const_name}: &[&str] = &[").unwrap();
+    for word in &words {
+        write!(f_dest, "{word:?},").unwrap();
+    }
     f_dest.write_all(b"];").unwrap();
+
+    // Indexes of the words in sorted order of the words, for looking up words by binary search.
+    let mut sorted_indexes = (0..words.len()).collect::<Vec<_>>();
+    sorted_indexes.sort_by_key(|&i| &words[i]);
+    write!(f_dest, "const SORTED_{const_name}: &[u16] = &[").unwrap();
+    for i in sorted_indexes {
+        write!(f_dest, "{i},").unwrap();
+    }
+    f_dest.write_all(b"];").unwrap();
+
+    // BIP39 mnemonic sentences are not made by picking words with dice, and they
+    // are most commonly 12 words long.
+    write!(
+        f_dest,
+        "pub const WORDLIST_{const_name}: Wordlist = Wordlist {{ \
+            name: {name:?}, \
+            words: WL_{const_name}, \
+            dice_digits: None, \
+            source_url: {source_url:?}, \
+            unique_prefix_len: {}, \
+            min_edit_distance: {}, \
+            default_num_words: 12, \
+            sorted_indexes: SORTED_{const_name} \
+        }};",
+        unique_prefix_len(&words),
+        min_edit_distance(&words),
+    )
+    .unwrap();
 }
 
+// `unique_prefix_len` and `min_edit_distance`, shared with the `pgen` crate.
+include!("wordlist_metrics.rs");
+
 fn main() {
     let out_dir = env::var("OUT_DIR").unwrap();
     let dest_path = Path::new(&out_dir).join("wordlists.rs");
     let f = File::create(dest_path).unwrap();
 
-    words_simple(&f, "WL_BIP39", "data/bip39_en_wordlist.txt");
+    const BIP39_WORDLISTS: &str = "https://github.com/bitcoin/bips/blob/master/bip-0039";
+
+    for (const_name, lang, name, fname) in [
+        ("BIP39", "en", "BIP39 English", "english"),
+        ("BIP39_CS", "cs", "BIP39 Czech", "czech"),
+        ("BIP39_ES", "es", "BIP39 Spanish", "spanish"),
+        ("BIP39_FR", "fr", "BIP39 French", "french"),
+        ("BIP39_IT", "it", "BIP39 Italian", "italian"),
+        ("BIP39_JA", "ja", "BIP39 Japanese", "japanese"),
+        ("BIP39_KO", "ko", "BIP39 Korean", "korean"),
+        ("BIP39_PT", "pt", "BIP39 Portuguese", "portuguese"),
+        (
+            "BIP39_ZH_HANS",
+            "zh_hans",
+            "BIP39 Chinese, Simplified",
+            "chinese_simplified",
+        ),
+        (
+            "BIP39_ZH_HANT",
+            "zh_hant",
+            "BIP39 Chinese, Traditional",
+            "chinese_traditional",
+        ),
+    ] {
+        // Each wordlist is only generated when its cargo feature, such as `bip39-en`, is enabled.
+        if env::var_os(format!("CARGO_FEATURE_BIP39_{}", lang.to_uppercase())).is_none() {
+            continue;
+        }
+        words_simple(
+            &f,
+            const_name,
+            &format!("data/bip39_{lang}_wordlist.txt"),
+            name,
+            &format!("{BIP39_WORDLISTS}/{fname}.txt"),
+        );
+    }
 }
@@ -1,5 +1,115 @@
 #![no_std]
 #![forbid(unsafe_code)]
 
+/// A wordlist, along with metadata about it.
+///
+/// Generated at build time, with the properties of the words computed from the wordlist data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wordlist {
+    /// Name of the wordlist
+    pub name: &'static str,
+    /// The words of the wordlist, in order
+    pub words: &'static [&'static str],
+    /// Number of six-sided dice to throw to pick a word, for wordlists made for use with dice.
+    /// Always `None` for the BIP39 wordlists, which have 2048 words and so cannot be used
+    /// with dice. Kept so that the wordlists have the same metadata as those of `eff-lexical-data`.
+    pub dice_digits: Option<u32>,
+    /// Where the wordlist data comes from
+    pub source_url: &'static str,
+    /// Smallest number of leading characters that uniquely identifies each word
+    pub unique_prefix_len: usize,
+    /// Smallest edit distance (Levenshtein distance) between any two words
+    pub min_edit_distance: usize,
+    /// Number of words to use for a passphrase, unless otherwise specified
+    pub default_num_words: usize,
+    /// Indexes of the words, ordered so that the words they point to are in sorted order
+    pub sorted_indexes: &'static [u16],
+}
+
 // https://doc.rust-lang.org/cargo/reference/build-scripts.html#case-study-code-generation
 include!(concat!(env!("OUT_DIR"), "/wordlists.rs"));
+
+impl Wordlist {
+    /// Look up the index of given word in the wordlist, which is the 11 bits that the word encodes.
+    ///
+    /// The word must be in the same form as the words of the wordlist, which is UTF-8 NFKD.
+    /// Uses binary search, and does not allocate.
+    pub fn index_of(&self, word: &str) -> Option<u16> {
+        let pos = self
+            .sorted_indexes
+            .binary_search_by(|&i| self.words[usize::from(i)].cmp(word))
+            .ok()?;
+        Some(self.sorted_indexes[pos])
+    }
+
+    /// Look up the index of the one word in the wordlist that starts with given prefix.
+    ///
+    /// Words in the BIP39 wordlists are uniquely identified by their first four letters,
+    /// so that is enough for finding the word. A word that matches exactly is always found,
+    /// even if it is also the beginning of some other word. Returns `None` if no word,
+    /// or more than one word, starts with the prefix.
+    pub fn index_of_prefix(&self, prefix: &str) -> Option<u16> {
+        if prefix.is_empty() {
+            return None;
+        }
+        // All words that start with the prefix come right after each other in sorted order,
+        // with the word equal to the prefix, if any, being the first of them.
+        let start = self
+            .sorted_indexes
+            .partition_point(|&i| self.words[usize::from(i)] < prefix);
+        let mut matches = self.sorted_indexes[start..]
+            .iter()
+            .copied()
+            .take_while(|&i| self.words[usize::from(i)].starts_with(prefix));
+        let first = matches.next()?;
+        if self.words[usize::from(first)] == prefix || matches.next().is_none() {
+            Some(first)
+        } else {
+            None
+        }
+    }
+}
+
+// The tests use wordlists that are enabled by default.
+#[cfg(all(
+    test,
+    feature = "bip39-en",
+    feature = "bip39-ja",
+    feature = "bip39-zh-hans"
+))]
+mod test {
+    use crate::{WORDLIST_BIP39, WORDLIST_BIP39_JA, WORDLIST_BIP39_ZH_HANS};
+
+    #[test]
+    fn finds_index_of_every_word() {
+        for wordlist in [WORDLIST_BIP39, WORDLIST_BIP39_JA, WORDLIST_BIP39_ZH_HANS] {
+            for (index, &word) in wordlist.words.iter().enumerate() {
+                assert_eq!(wordlist.index_of(word), Some(index as u16));
+            }
+        }
+    }
+
+    #[test]
+    fn does_not_find_index_of_unknown_word() {
+        assert_eq!(WORDLIST_BIP39.index_of("abuot"), None);
+        assert_eq!(WORDLIST_BIP39.index_of("aban"), None);
+        assert_eq!(WORDLIST_BIP39.index_of(""), None);
+    }
+
+    #[test]
+    fn finds_index_of_unique_prefix() {
+        for (index, &word) in WORDLIST_BIP39.words.iter().enumerate() {
+            let prefix = &word[..word.len().min(4)];
+            assert_eq!(WORDLIST_BIP39.index_of_prefix(prefix), Some(index as u16));
+        }
+        assert_eq!(WORDLIST_BIP39.index_of_prefix("aban"), Some(0));
+        assert_eq!(WORDLIST_BIP39.index_of_prefix("zoo"), Some(2047));
+    }
+
+    #[test]
+    fn does_not_find_index_of_ambiguous_or_unknown_prefix() {
+        assert_eq!(WORDLIST_BIP39.index_of_prefix("ab"), None);
+        assert_eq!(WORDLIST_BIP39.index_of_prefix("abx"), None);
+        assert_eq!(WORDLIST_BIP39.index_of_prefix(""), None);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::env;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
@@ -5,30 +6,179 @@ use std::path::Path;
 
 // https://doc.rust-lang.org/cargo/reference/build-scripts.html#case-study-code-generation
 
-/// Extract words from the EFF wordlists
-fn words_eff(mut f_dest: &File, const_name: &str, fname_src: &str) {
-    write!(f_dest, "pub const {const_name}: &[&str] = &[").unwrap();
-
-    let f_src = BufReader::new(File::open(fname_src).unwrap());
-    for line in f_src.lines() {
-        match line {
-            Ok(line) => {
-                let word = line.split('\t').nth(1).unwrap();
-                write!(f_dest, "\"{word}\",").unwrap();
-            }
-            Err(_e) => panic!("Unable to read line from internal file"),
+/// Stop the build, pointing at the offending line of the wordlist data.
+fn fail(fname_src: &str, line_num: usize, msg: &str) -> ! {
+    panic!("{fname_src}:{line_num}: {msg}");
+}
+
+/// Extract words from the EFF wordlists, and generate a `Wordlist` with metadata about them.
+///
+/// Every line must consist of the dice digits of the word, a tab, and the word.
+/// The dice digits must be sequential, starting from all ones (`11111`, `11112`, ...),
+/// so that there is exactly one word for every possible throw of the dice.
+/// The words must be unique, and sorted.
+fn words_eff(
+    mut f_dest: &File,
+    const_name: &str,
+    fname_src: &str,
+    name: &str,
+    source_url: &str,
+    dice_digits: u32,
+    default_num_words: usize,
+) {
+    let expected_num_words = 6usize.pow(dice_digits);
+
+    let mut words = vec![];
+    let mut seen = HashSet::new();
+    let f_src = BufReader::new(
+        File::open(fname_src).unwrap_or_else(|e| panic!("Unable to open {fname_src}: {e}")),
+    );
+    for (i, line) in f_src.lines().enumerate() {
+        let line_num = i + 1;
+        let line = line
+            .unwrap_or_else(|e| fail(fname_src, line_num, &format!("Unable to read line: {e}")));
+
+        let Some((digits, word)) = line.split_once('\t') else {
+            fail(
+                fname_src,
+                line_num,
+                "Expected dice digits, a tab, and a word",
+            );
+        };
+        let expected_digits = dice_digits_for_index(words.len(), dice_digits);
+        if digits != expected_digits {
+            fail(
+                fname_src,
+                line_num,
+                &format!("Expected dice digits {expected_digits:?}, found {digits:?}"),
+            );
+        }
+        validate_word(fname_src, line_num, word);
+        if !seen.insert(word.to_string()) {
+            fail(fname_src, line_num, &format!("Duplicate word {word:?}"));
         }
+        // Words are looked up by binary search, see `Wordlist::dice_for_word`.
+        if words
+            .last()
+            .is_some_and(|prev: &String| prev.as_str() > word)
+        {
+            fail(
+                fname_src,
+                line_num,
+                &format!("Word {word:?} is not in sorted order"),
+            );
+        }
+        words.push(word.to_string());
+    }
+
+    if words.len() != expected_num_words {
+        fail(
+            fname_src,
+            words.len(),
+            &format!(
+                "Expected {expected_num_words} words for {dice_digits} dice, found {}",
+                words.len()
+            ),
+        );
     }
 
+    // Words are written using `Debug` formatting, which escapes them as Rust string literals.
+    write!(f_dest, "pub const WL_{const_name}: &[&str] = &[").unwrap();
+    for word in &words {
+        write!(f_dest, "{word:?},").unwrap();
+    }
     f_dest.write_all(b"];").unwrap();
+
+    write!(
+        f_dest,
+        "pub const WORDLIST_{const_name}: Wordlist = Wordlist {{ \
+            name: {name:?}, \
+            words: WL_{const_name}, \
+            dice_digits: Some({dice_digits}), \
+            source_url: {source_url:?}, \
+            unique_prefix_len: {}, \
+            min_edit_distance: {}, \
+            default_num_words: {default_num_words} \
+        }};",
+        unique_prefix_len(&words),
+        min_edit_distance(&words),
+    )
+    .unwrap();
 }
 
+/// Dice digits of the word with given zero-based index, such as `"11111"` for 0 with five dice.
+fn dice_digits_for_index(mut index: usize, dice_digits: u32) -> String {
+    let mut digits = vec![b'1'; dice_digits as usize];
+    for digit in digits.iter_mut().rev() {
+        *digit += (index % 6) as u8;
+        index /= 6;
+    }
+    String::from_utf8(digits).unwrap()
+}
+
+/// Check that a word is non-empty, and does not contain whitespace or control characters.
+fn validate_word(fname_src: &str, line_num: usize, word: &str) {
+    if word.is_empty() {
+        fail(fname_src, line_num, "Empty word");
+    }
+    if word.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        fail(
+            fname_src,
+            line_num,
+            &format!("Word {word:?} contains whitespace or control characters"),
+        );
+    }
+}
+
+// `unique_prefix_len` and `min_edit_distance`, shared with the `pgen` crate.
+include!("wordlist_metrics.rs");
+
 fn main() {
     let out_dir = env::var("OUT_DIR").unwrap();
     let dest_path = Path::new(&out_dir).join("wordlists.rs");
     let f = File::create(dest_path).unwrap();
 
-    words_eff(&f, "WL_AUTOCOMPLETE", "data/eff_short_wordlist_2_0.txt");
-    words_eff(&f, "WL_LONG", "data/eff_large_wordlist.txt");
-    words_eff(&f, "WL_SHORT", "data/eff_short_wordlist_1.txt");
+    // Each wordlist is only generated when the cargo feature of the same name is enabled,
+    // so that users who only need one of the wordlists do not have to pay for the others.
+    for (feature, const_name, fname_src, name, source_url, dice_digits, default_num_words) in [
+        (
+            "EFF_AUTOCOMPLETE",
+            "AUTOCOMPLETE",
+            "data/eff_short_wordlist_2_0.txt",
+            "EFF's Short Wordlist #2",
+            "https://www.eff.org/files/2016/09/08/eff_short_wordlist_2_0.txt",
+            4,
+            12,
+        ),
+        (
+            "EFF_LONG",
+            "LONG",
+            "data/eff_large_wordlist.txt",
+            "EFF's Long Wordlist",
+            "https://www.eff.org/files/2016/07/18/eff_large_wordlist.txt",
+            5,
+            10,
+        ),
+        (
+            "EFF_SHORT",
+            "SHORT",
+            "data/eff_short_wordlist_1.txt",
+            "EFF's Short Wordlist #1",
+            "https://www.eff.org/files/2016/09/08/eff_short_wordlist_1.txt",
+            4,
+            12,
+        ),
+    ] {
+        if env::var_os(format!("CARGO_FEATURE_{feature}")).is_some() {
+            words_eff(
+                &f,
+                const_name,
+                fname_src,
+                name,
+                source_url,
+                dice_digits,
+                default_num_words,
+            );
+        }
+    }
 }
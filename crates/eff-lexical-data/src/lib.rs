@@ -1,5 +1,167 @@
 #![no_std]
 #![forbid(unsafe_code)]
 
+/// A wordlist, along with metadata about it.
+///
+/// Generated at build time, with the properties of the words computed from the wordlist data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wordlist {
+    /// Name of the wordlist
+    pub name: &'static str,
+    /// The words of the wordlist, in order
+    pub words: &'static [&'static str],
+    /// Number of six-sided dice to throw to pick a word, for wordlists made for use with dice
+    pub dice_digits: Option<u32>,
+    /// Where the wordlist data comes from
+    pub source_url: &'static str,
+    /// Smallest number of leading characters that uniquely identifies each word
+    pub unique_prefix_len: usize,
+    /// Smallest edit distance (Levenshtein distance) between any two words
+    pub min_edit_distance: usize,
+    /// Number of words to use for a passphrase, unless otherwise specified
+    pub default_num_words: usize,
+}
+
 // https://doc.rust-lang.org/cargo/reference/build-scripts.html#case-study-code-generation
 include!(concat!(env!("OUT_DIR"), "/wordlists.rs"));
+
+/// Maximum number of dice digits supported by [`DiceCode`].
+pub const MAX_DICE_DIGITS: usize = 5;
+
+/// The numbers of eyes shown on a throw of dice, such as `11111`, that picks a word from a wordlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiceCode {
+    digits: [u8; MAX_DICE_DIGITS],
+    len: usize,
+}
+
+impl DiceCode {
+    /// Get the dice code of the word with given zero-based index, in a wordlist for `num_dice` dice.
+    ///
+    /// Returns `None` if `num_dice` is more than [`MAX_DICE_DIGITS`],
+    /// or if the index is out of range for the number of dice.
+    pub fn from_index(mut index: usize, num_dice: u32) -> Option<Self> {
+        let len = num_dice as usize;
+        if len > MAX_DICE_DIGITS || index >= 6usize.pow(num_dice) {
+            return None;
+        }
+        let mut digits = [b'1'; MAX_DICE_DIGITS];
+        for digit in digits[..len].iter_mut().rev() {
+            *digit += (index % 6) as u8;
+            index /= 6;
+        }
+        Some(Self { digits, len })
+    }
+
+    /// Parse a dice code, such as `"11111"`. Every digit must be between 1 and 6.
+    pub fn parse(digits: &str) -> Option<Self> {
+        let len = digits.len();
+        if len == 0 || len > MAX_DICE_DIGITS || !digits.bytes().all(|b| (b'1'..=b'6').contains(&b))
+        {
+            return None;
+        }
+        let mut buf = [b'1'; MAX_DICE_DIGITS];
+        buf[..len].copy_from_slice(digits.as_bytes());
+        Some(Self { digits: buf, len })
+    }
+
+    /// Zero-based index of the word that this dice code picks.
+    pub fn index(&self) -> usize {
+        self.digits[..self.len]
+            .iter()
+            .fold(0, |index, &digit| index * 6 + usize::from(digit - b'1'))
+    }
+
+    /// Number of dice in the dice code.
+    pub fn num_dice(&self) -> u32 {
+        self.len as u32
+    }
+
+    /// The dice code as a string of digits, such as `"11111"`.
+    pub fn as_str(&self) -> &str {
+        // Only contains the ASCII digits 1 to 6, so this cannot fail.
+        core::str::from_utf8(&self.digits[..self.len]).unwrap()
+    }
+}
+
+impl core::fmt::Display for DiceCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Wordlist {
+    /// Look up the word for given dice code, such as `"11111"` for the first word.
+    ///
+    /// Returns `None` if the dice code is not valid for this wordlist.
+    pub fn word_for_dice(&self, digits: &str) -> Option<&'static str> {
+        let code = DiceCode::parse(digits)?;
+        if Some(code.num_dice()) != self.dice_digits {
+            return None;
+        }
+        self.words.get(code.index()).copied()
+    }
+
+    /// Look up the dice code for given word.
+    ///
+    /// Returns `None` if the word is not in this wordlist.
+    pub fn dice_for_word(&self, word: &str) -> Option<DiceCode> {
+        // The build script ensures that the words are sorted.
+        let index = self.words.binary_search(&word).ok()?;
+        DiceCode::from_index(index, self.dice_digits?)
+    }
+}
+
+// The tests use wordlists that are enabled by default.
+#[cfg(all(
+    test,
+    feature = "eff-autocomplete",
+    feature = "eff-long",
+    feature = "eff-short"
+))]
+mod test {
+    use crate::{DiceCode, WORDLIST_AUTOCOMPLETE, WORDLIST_LONG, WORDLIST_SHORT};
+
+    #[test]
+    fn looks_up_words_for_dice() {
+        assert_eq!(WORDLIST_LONG.word_for_dice("11111"), Some("abacus"));
+        assert_eq!(WORDLIST_LONG.word_for_dice("11112"), Some("abdomen"));
+        assert_eq!(WORDLIST_LONG.word_for_dice("66666"), Some("zoom"));
+        assert_eq!(WORDLIST_SHORT.word_for_dice("1111"), Some("acid"));
+        assert_eq!(
+            WORDLIST_AUTOCOMPLETE.word_for_dice("1111"),
+            Some("aardvark")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_dice() {
+        assert_eq!(WORDLIST_LONG.word_for_dice("1111"), None);
+        assert_eq!(WORDLIST_LONG.word_for_dice("11117"), None);
+        assert_eq!(WORDLIST_LONG.word_for_dice("11110"), None);
+        assert_eq!(WORDLIST_SHORT.word_for_dice("11111"), None);
+        assert_eq!(WORDLIST_SHORT.word_for_dice(""), None);
+    }
+
+    #[test]
+    fn looks_up_dice_for_words() {
+        for wordlist in [WORDLIST_AUTOCOMPLETE, WORDLIST_LONG, WORDLIST_SHORT] {
+            for (index, &word) in wordlist.words.iter().enumerate() {
+                let code = wordlist.dice_for_word(word).unwrap();
+                assert_eq!(code.index(), index);
+                assert_eq!(wordlist.word_for_dice(code.as_str()), Some(word));
+            }
+            assert_eq!(wordlist.dice_for_word("notaword"), None);
+        }
+    }
+
+    #[test]
+    fn converts_between_dice_codes_and_indexes() {
+        assert_eq!(DiceCode::from_index(0, 5).unwrap().as_str(), "11111");
+        assert_eq!(DiceCode::from_index(7, 4).unwrap().as_str(), "1122");
+        assert_eq!(DiceCode::from_index(1295, 4).unwrap().as_str(), "6666");
+        assert_eq!(DiceCode::from_index(1296, 4), None);
+        assert_eq!(DiceCode::from_index(0, 6), None);
+        assert_eq!(DiceCode::parse("1122").unwrap().index(), 7);
+    }
+}
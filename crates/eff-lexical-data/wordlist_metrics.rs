@@ -0,0 +1 @@
+../pgen/wordlist_metrics.rs
\ No newline at end of file
@@ -0,0 +1,46 @@
+/*
+ * Copyright (c) 2024 Erik Nordstrøm <erik@nordstroem.no>
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+//! Compare the two implementations of chunking BIP39 entropy into 11 bit groups.
+//!
+//! Run with `cargo bench -p pgen --features bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use pgen::bip39_algorithm::bench::{chunk_to_11_bit_groups, chunk_to_11_bit_groups_alt_via_u128};
+
+fn bench_chunking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chunk_to_11_bit_groups");
+    for ent_num_bytes in [16, 20, 24, 28, 32] {
+        // Fixed, but not entirely regular, input so that runs are comparable.
+        let ent = (0..ent_num_bytes)
+            .map(|i| (i as u8).wrapping_mul(157) ^ 0x5a)
+            .collect::<Vec<_>>();
+        group.bench_with_input(
+            BenchmarkId::new("bit_by_bit", ent_num_bytes),
+            &ent,
+            |b, ent| b.iter(|| chunk_to_11_bit_groups(black_box(ent))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("via_u128", ent_num_bytes),
+            &ent,
+            |b, ent| b.iter(|| chunk_to_11_bit_groups_alt_via_u128(black_box(ent))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_chunking);
+criterion_main!(benches);
@@ -0,0 +1,157 @@
+/*
+ * Copyright (c) 2024 Erik Nordstrøm <erik@nordstroem.no>
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+#![forbid(unsafe_code)]
+
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use thiserror::Error;
+
+/// Order of the secp256k1 curve. Master private keys must be non-zero and less than this.
+const SECP256K1_N: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("Seed does not give a valid BIP32 master private key")]
+    InvalidMasterKey,
+}
+
+/// Bitcoin network that an extended key is serialized for.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Network {
+    /// Serialized as `xprv...`
+    Mainnet,
+    /// Serialized as `tprv...`
+    Testnet,
+}
+
+impl Network {
+    /// Version bytes for serialization of extended private keys.
+    fn xprv_version(self) -> [u8; 4] {
+        match self {
+            Self::Mainnet => [0x04, 0x88, 0xad, 0xe4],
+            Self::Testnet => [0x04, 0x35, 0x83, 0x94],
+        }
+    }
+}
+
+/// BIP32 master extended private key.
+pub struct MasterKey {
+    key: [u8; 32],
+    chain_code: [u8; 32],
+}
+
+impl MasterKey {
+    /// Derive the BIP32 master extended private key from a seed, such as a BIP39 seed.
+    ///
+    /// See <https://en.bitcoin.it/wiki/BIP_0032#Master_key_generation> for details.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, Error> {
+        // HMAC-SHA512 accepts keys of any length, so this cannot fail.
+        let mut mac = Hmac::<Sha512>::new_from_slice(b"Bitcoin seed").unwrap();
+        mac.update(seed);
+        let i = mac.finalize().into_bytes();
+        let (il, ir) = i.split_at(32);
+        // In the (astronomically unlikely) case that IL is 0 or not less than n,
+        // the master key is invalid. Byte arrays compare lexicographically,
+        // which is the same as comparing them as big-endian numbers.
+        if il.iter().all(|&b| b == 0) || il >= SECP256K1_N.as_slice() {
+            return Err(Error::InvalidMasterKey);
+        }
+        Ok(Self {
+            key: il.try_into().unwrap(),
+            chain_code: ir.try_into().unwrap(),
+        })
+    }
+
+    /// Serialize as Base58Check encoded extended private key (`xprv...` or `tprv...`).
+    ///
+    /// See <https://en.bitcoin.it/wiki/BIP_0032#Serialization_format> for details.
+    pub fn to_base58check(&self, network: Network) -> String {
+        let mut data = Vec::with_capacity(78);
+        data.extend_from_slice(&network.xprv_version());
+        // Depth, parent key fingerprint and child number are all zero for the master key.
+        data.push(0);
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&self.chain_code);
+        // Private keys are prefixed with a zero byte to be the same length as public keys.
+        data.push(0);
+        data.extend_from_slice(&self.key);
+        bs58::encode(data).with_check().into_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bip32::{MasterKey, Network};
+    use crate::bip39_algorithm::seed_from_mnemonic;
+    use crate::test_util::hex_to_bytes;
+    use test_case::test_case;
+
+    // Test vectors 1, 2 and 3 from <https://en.bitcoin.it/wiki/BIP_0032_TestVectors>.
+    #[test_case("000102030405060708090a0b0c0d0e0f", "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi"; "BIP32 test vector 1")]
+    #[test_case("fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542", "xprv9s21ZrQH143K31xYSDQpPDxsXRTUcvj2iNHm5NUtrGiGG5e2DtALGdso3pGz6ssrdK4PFmM8NSpSBHNqPqm55Qn3LqFtT2emdEXVYsCzC2U"; "BIP32 test vector 2")]
+    #[test_case("4b381541583be4423346c643850da4b320e46a87ae3d2a4e6da11eba819cd4acba45d239319ac14f863b8d5ab5a0d0c64d2e8a1e7d1457df2e5a3c51c73235be", "xprv9s21ZrQH143K25QhxbucbDDuQ4naNntJRi4KUfWT7xo4EKsHt2QJDu7KXp1A3u7Bi1j8ph3EGsZ9Xvz9dGuVrtHHs7pXeTzjuxBrCmmhgC6"; "BIP32 test vector 3")]
+    fn derives_master_key_from_seed_correctly(seed_hex: &str, expected_xprv: &str) {
+        let master_key = MasterKey::from_seed(&hex_to_bytes(seed_hex)).unwrap();
+        assert_eq!(expected_xprv, master_key.to_base58check(Network::Mainnet));
+    }
+
+    #[test]
+    fn serializes_testnet_master_key_as_tprv() {
+        let master_key =
+            MasterKey::from_seed(&hex_to_bytes("000102030405060708090a0b0c0d0e0f")).unwrap();
+        assert!(master_key
+            .to_base58check(Network::Testnet)
+            .starts_with("tprv"));
+    }
+
+    // English test vectors from <https://github.com/trezor/python-mnemonic/blob/b57a5ad77a981e743f4167ab2f7927a55c1e82a8/vectors.json>.
+    // All of these use the BIP39 passphrase "TREZOR".
+    #[test_case("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about", "xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF")]
+    #[test_case("legal winner thank year wave sausage worth useful legal winner thank yellow", "xprv9s21ZrQH143K2gA81bYFHqU68xz1cX2APaSq5tt6MFSLeXnCKV1RVUJt9FWNTbrrryem4ZckN8k4Ls1H6nwdvDTvnV7zEXs2HgPezuVccsq")]
+    #[test_case("letter advice cage absurd amount doctor acoustic avoid letter advice cage above", "xprv9s21ZrQH143K2shfP28KM3nr5Ap1SXjz8gc2rAqqMEynmjt6o1qboCDpxckqXavCwdnYds6yBHZGKHv7ef2eTXy461PXUjBFQg6PrwY4Gzq")]
+    #[test_case("zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong", "xprv9s21ZrQH143K2V4oox4M8Zmhi2Fjx5XK4Lf7GKRvPSgydU3mjZuKGCTg7UPiBUD7ydVPvSLtg9hjp7MQTYsW67rZHAXeccqYqrsx8LcXnyd")]
+    #[test_case("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon agent", "xprv9s21ZrQH143K3mEDrypcZ2usWqFgzKB6jBBx9B6GfC7fu26X6hPRzVjzkqkPvDqp6g5eypdk6cyhGnBngbjeHTe4LsuLG1cCmKJka5SMkmU")]
+    #[test_case("legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful legal will", "xprv9s21ZrQH143K3Lv9MZLj16np5GzLe7tDKQfVusBni7toqJGcnKRtHSxUwbKUyUWiwpK55g1DUSsw76TF1T93VT4gz4wt5RM23pkaQLnvBh7")]
+    #[test_case("letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter always", "xprv9s21ZrQH143K3VPCbxbUtpkh9pRG371UCLDz3BjceqP1jz7XZsQ5EnNkYAEkfeZp62cDNj13ZTEVG1TEro9sZ9grfRmcYWLBhCocViKEJae")]
+    #[test_case("zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo when", "xprv9s21ZrQH143K36Ao5jHRVhFGDbLP6FCx8BEEmpru77ef3bmA928BxsqvVM27WnvvyfWywiFN8K6yToqMaGYfzS6Db1EHAXT5TuyCLBXUfdm")]
+    #[test_case("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art", "xprv9s21ZrQH143K32qBagUJAMU2LsHg3ka7jqMcV98Y7gVeVyNStwYS3U7yVVoDZ4btbRNf4h6ibWpY22iRmXq35qgLs79f312g2kj5539ebPM")]
+    #[test_case("legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth title", "xprv9s21ZrQH143K3Y1sd2XVu9wtqxJRvybCfAetjUrMMco6r3v9qZTBeXiBZkS8JxWbcGJZyio8TrZtm6pkbzG8SYt1sxwNLh3Wx7to5pgiVFU")]
+    #[test_case("letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless", "xprv9s21ZrQH143K3CSnQNYC3MqAAqHwxeTLhDbhF43A4ss4ciWNmCY9zQGvAKUSqVUf2vPHBTSE1rB2pg4avopqSiLVzXEU8KziNnVPauTqLRo")]
+    #[test_case("zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote", "xprv9s21ZrQH143K2WFF16X85T2QCpndrGwx6GueB72Zf3AHwHJaknRXNF37ZmDrtHrrLSHvbuRejXcnYxoZKvRquTPyp2JiNG3XcjQyzSEgqCB")]
+    #[test_case("ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic", "xprv9s21ZrQH143K2oZ9stBYpoaZ2ktHj7jLz7iMqpgg1En8kKFTXJHsjxry1JbKH19YrDTicVwKPehFKTbmaxgVEc5TpHdS1aYhB2s9aFJBeJH")]
+    #[test_case("gravity machine north sort system female filter attitude volume fold club stay feature office ecology stable narrow fog", "xprv9s21ZrQH143K3uT8eQowUjsxrmsA9YUuQQK1RLqFufzybxD6DH6gPY7NjJ5G3EPHjsWDrs9iivSbmvjc9DQJbJGatfa9pv4MZ3wjr8qWPAK")]
+    #[test_case("hamster diagram private dutch cause delay private meat slide toddler razor book happy fancy gospel tennis maple dilemma loan word shrug inflict delay length", "xprv9s21ZrQH143K2XTAhys3pMNcGn261Fi5Ta2Pw8PwaVPhg3D8DWkzWQwjTJfskj8ofb81i9NP2cUNKxwjueJHHMQAnxtivTA75uUFqPFeWzk")]
+    #[test_case("scheme spot photo card baby mountain device kick cradle pact join borrow", "xprv9s21ZrQH143K3FperxDp8vFsFycKCRcJGAFmcV7umQmcnMZaLtZRt13QJDsoS5F6oYT6BB4sS6zmTmyQAEkJKxJ7yByDNtRe5asP2jFGhT6")]
+    #[test_case("horn tenant knee talent sponsor spell gate clip pulse soap slush warm silver nephew swap uncle crack brave", "xprv9s21ZrQH143K3R1SfVZZLtVbXEB9ryVxmVtVMsMwmEyEvgXN6Q84LKkLRmf4ST6QrLeBm3jQsb9gx1uo23TS7vo3vAkZGZz71uuLCcywUkt")]
+    #[test_case("panda eyebrow bullet gorilla call smoke muffin taste mesh discover soft ostrich alcohol speed nation flash devote level hobby quick inner drive ghost inside", "xprv9s21ZrQH143K2WNnKmssvZYM96VAr47iHUQUTUyUXH3sAGNjhJANddnhw3i3y3pBbRAVk5M5qUGFr4rHbEWwXgX4qrvrceifCYQJbbFDems")]
+    #[test_case("cat swing flag economy stadium alone churn speed unique patch report train", "xprv9s21ZrQH143K4G28omGMogEoYgDQuigBo8AFHAGDaJdqQ99QKMQ5J6fYTMfANTJy6xBmhvsNZ1CJzRZ64PWbnTFUn6CDV2FxoMDLXdk95DQ")]
+    #[test_case("light rule cinnamon wrap drastic word pride squirrel upgrade then income fatal apart sustain crack supply proud access", "xprv9s21ZrQH143K3wtsvY8L2aZyxkiWULZH4vyQE5XkHTXkmx8gHo6RUEfH3Jyr6NwkJhvano7Xb2o6UqFKWHVo5scE31SGDCAUsgVhiUuUDyh")]
+    #[test_case("all hour make first leader extend hole alien behind guard gospel lava path output census museum junior mass reopen famous sing advance salt reform", "xprv9s21ZrQH143K3rEfqSM4QZRVmiMuSWY9wugscmaCjYja3SbUD3KPEB1a7QXJoajyR2T1SiXU7rFVRXMV9XdYVSZe7JoUXdP4SRHTxsT1nzm")]
+    #[test_case("vessel ladder alter error federal sibling chat ability sun glass valve picture", "xprv9s21ZrQH143K2QWV9Wn8Vvs6jbqfF1YbTCdURQW9dLFKDovpKaKrqS3SEWsXCu6ZNky9PSAENg6c9AQYHcg4PjopRGGKmdD313ZHszymnps")]
+    #[test_case("scissors invite lock maple supreme raw rapid void congress muscle digital elegant little brisk hair mango congress clump", "xprv9s21ZrQH143K4aERa2bq7559eMCCEs2QmmqVjUuzfy5eAeDX4mqZffkYwpzGQRE2YEEeLVRoH4CSHxianrFaVnMN2RYaPUZJhJx8S5j6puX")]
+    #[test_case("void come effort suffer camp survey warrior heavy shoot primary clutch crush open amazing screen patrol group space point ten exist slush involve unfold", "xprv9s21ZrQH143K39rnQJknpH1WEPFJrzmAqqasiDcVrNuk926oizzJDDQkdiTvNPr2FYDYzWgiMiC63YmfPAa2oPyNB23r2g7d1yiK6WpqaQS")]
+    fn derives_master_key_from_bip39_mnemonic_correctly(mnemonic: &str, expected_xprv: &str) {
+        let words = mnemonic.split(' ').collect::<Vec<_>>();
+        let seed = seed_from_mnemonic(&words, "TREZOR");
+        let master_key = MasterKey::from_seed(&seed).unwrap();
+        assert_eq!(expected_xprv, master_key.to_base58check(Network::Mainnet));
+    }
+}
@@ -1 +1,8 @@
-mod bip39_algorithm;
+pub mod bip32;
+pub mod bip39_algorithm;
+pub mod passphrase;
+pub mod policy;
+pub mod wordlist;
+
+#[cfg(test)]
+mod test_util;
@@ -16,34 +16,253 @@
 
 #![forbid(unsafe_code)]
 
-use bip39_lexical_data::WL_BIP39;
-use clap::{Parser, ValueEnum};
-use eff_lexical_data::{WL_AUTOCOMPLETE, WL_LONG, WL_SHORT};
-use rand::thread_rng;
-use rand::Rng;
-use std::io::{stdin, stdout, Write};
+use anyhow::Context;
+use bip39_lexical_data::WORDLIST_BIP39;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use eff_lexical_data::DiceCode;
+use pgen::bip32::{MasterKey, Network};
+use pgen::bip39_algorithm::{
+    checksum, ent_num_bytes_for_ms_len, entropy_from_mnemonic, get_11_bits_from_word,
+    mnemonic_from_entropy, ms_len_for_ent_num_bytes, ms_len_for_entropy_bits, recover_mnemonic,
+    seed_from_mnemonic, word_indices_from_entropy, DiceEntropyCollector, Language,
+    ALLOWABLE_MS_LENGTHS, UNKNOWN_WORD_PLACEHOLDER,
+};
+use pgen::passphrase::{
+    self, num_words_for_entropy_bits, Capitalization, PassphraseGenerator, Separator,
+};
+use pgen::policy::Policy;
+use pgen::wordlist::{analyze, words_unchecked, LoadedWordlist, Wordlist};
+use rand::{thread_rng, CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{read_to_string, stdin, stdout, BufRead, Write};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
+#[command(group(ArgGroup::new("bip39_outputs").multiple(true).args(["print_bip39_seed", "print_bip32_xprv"])))]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
     /// Use physical six-sided dice instead of letting the computer pick words
     #[arg(short = 'd', long = "dice")]
     use_physical_dice: bool,
     /// Select wordlist to use
     #[arg(short = 'w', long = "wordlist", value_enum, default_value_t)]
     use_wlist: WordlistChoice,
+    /// Use wordlist from file instead of one of the built-in wordlists
+    ///
+    /// The file must contain either one word per line, or one word per line preceded by
+    /// its dice digits and a tab, like the EFF wordlists. Only the latter can be used with dice.
+    /// Words must be unique, non-empty, ASCII, and must not contain whitespace.
+    #[arg(
+        long = "wordlist-file",
+        value_name = "PATH",
+        conflicts_with = "use_wlist"
+    )]
+    wordlist_file: Option<PathBuf>,
+    /// Select language of BIP39 wordlist to use. Requires BIP39 wordlist
+    #[arg(short = 'l', long = "language", value_enum, default_value_t)]
+    language: LanguageChoice,
     /// Specify the number of passphrases to generate k
     #[arg(short, default_value_t = 1, value_name = "k")]
     k: u32,
     /// Specify the number of words to use
     #[arg(short, value_name = "n")]
     n: Option<usize>,
+    /// Use the smallest number of words that gives at least the given number of bits of entropy
+    ///
+    /// With the BIP39 wordlist, the shortest mnemonic sentence with at least that many bits
+    /// of entropy is used, so at most 256 bits can be asked for.
+    #[arg(long = "bits", value_name = "N", value_parser = clap::value_parser!(u32).range(1..), conflicts_with_all = ["n", "entropy_hex"])]
+    bits: Option<u32>,
+    /// Select separator to put between words
+    #[arg(
+        long = "separator",
+        value_name = "SEPARATOR",
+        value_enum,
+        default_value_t
+    )]
+    separator: SeparatorChoice,
+    /// Select how to capitalize words
+    #[arg(long = "capitalize", value_name = "STYLE", value_enum, default_value_t)]
+    capitalization: CapitalizationChoice,
+    /// Only use words of at most this many characters from the wordlist
+    #[arg(long = "max-word-length", value_name = "N")]
+    max_word_length: Option<usize>,
+    /// Passphrases must be at least this many characters long. Shorter ones are picked again
+    #[arg(
+        long = "min-length",
+        value_name = "N",
+        help_heading = "Password policy"
+    )]
+    min_length: Option<usize>,
+    /// Passphrases must be at most this many characters long. Longer ones are picked again
+    #[arg(
+        long = "max-length",
+        value_name = "N",
+        help_heading = "Password policy"
+    )]
+    max_length: Option<usize>,
+    /// Passphrases must contain a digit. One is inserted at random if needed
+    #[arg(long = "require-digit", help_heading = "Password policy")]
+    require_digit: bool,
+    /// Passphrases must contain an uppercase letter. One is inserted at random if needed
+    #[arg(long = "require-uppercase", help_heading = "Password policy")]
+    require_uppercase: bool,
+    /// Passphrases must contain a symbol. One is inserted at random if needed
+    #[arg(long = "require-symbol", help_heading = "Password policy")]
+    require_symbol: bool,
+    /// Read password policy from file, instead of giving it with the options above
+    ///
+    /// The file has one `<setting> = <value>` per line. The settings are `min-length`,
+    /// `max-length`, `require-digit`, `require-uppercase` and `require-symbol`.
+    #[arg(
+        long = "policy-file",
+        value_name = "PATH",
+        help_heading = "Password policy",
+        conflicts_with_all = ["min_length", "max_length", "require_digit", "require_uppercase", "require_symbol"]
+    )]
+    policy_file: Option<PathBuf>,
+    /// Select output format
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t,
+        conflicts_with = "calculate_entropy"
+    )]
+    format: OutputFormat,
     /// Calculate and print the entropy for the passphrase(s) that would be generated with the given settings
+    ///
+    /// Prints a report with the entropy of each part of the passphrases, the number of possible
+    /// passphrases, their average length, and the average time it would take to crack them.
     #[arg(short = 'e')]
     calculate_entropy: bool,
+    /// Number of guesses per second to estimate the time to crack passphrases at, with `-e`.
+    /// Can be given more than once
+    ///
+    /// Default: 10000, roughly a slow password hash such as bcrypt, and 10000000000,
+    /// roughly a fast hash such as SHA-256, attacked with a single GPU.
+    #[arg(
+        long = "guess-rate",
+        value_name = "GUESSES_PER_SECOND",
+        value_parser = parse_guess_rate,
+        requires = "calculate_entropy"
+    )]
+    guess_rates: Vec<f64>,
+    /// Encode the given entropy (hex) as a BIP39 mnemonic sentence instead of generating random entropy. Requires BIP39 wordlist
+    ///
+    /// The entropy must be 16, 20, 24, 28 or 32 bytes, giving a mnemonic sentence of 12, 15, 18, 21 or 24 words respectively.
+    #[arg(long = "entropy-hex", value_name = "HEX", conflicts_with_all = ["use_physical_dice", "n", "k"])]
+    entropy_hex: Option<String>,
+    /// Also print the BIP39 seed (hex) of each generated mnemonic sentence. Requires BIP39 wordlist
+    #[arg(long = "bip39-seed")]
+    print_bip39_seed: bool,
+    /// Also print the BIP32 master extended private key of each generated mnemonic sentence. Requires BIP39 wordlist
+    #[arg(long = "bip32-xprv")]
+    print_bip32_xprv: bool,
+    /// Serialize BIP32 master extended private keys for testnet (tprv) instead of mainnet (xprv)
+    #[arg(long = "testnet", requires = "print_bip32_xprv")]
+    testnet: bool,
+    /// Passphrase to use when deriving the BIP39 seed, sometimes called the "25th word"
+    #[arg(
+        long = "bip39-passphrase",
+        value_name = "PASSPHRASE",
+        default_value = "",
+        requires = "bip39_outputs"
+    )]
+    bip39_passphrase: String,
+    /// INSECURE: Seed the random number generator, for reproducible output in tests
+    ///
+    /// The same seed always gives the same passphrases, so anyone who knows or guesses the seed
+    /// can recreate them. Never use this for passphrases that are meant to be secret.
+    #[arg(
+        long = "seed",
+        value_name = "SEED",
+        hide = true,
+        conflicts_with = "use_physical_dice"
+    )]
+    seed: Option<u64>,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Validate a BIP39 mnemonic sentence read from stdin
+    ///
+    /// Checks that the number of words is one of 12, 15, 18, 21 or 24,
+    /// that every word is in the BIP39 wordlist, and that the checksum matches.
+    Validate {
+        /// Language of the BIP39 mnemonic sentence
+        #[arg(short = 'l', long = "language", value_enum, default_value_t)]
+        language: LanguageChoice,
+    },
+    /// Derive the BIP39 seed (hex) from a BIP39 mnemonic sentence read from stdin
+    ///
+    /// The mnemonic sentence is validated first, in the same way as with `validate`.
+    Seed {
+        /// Language of the BIP39 mnemonic sentence
+        #[arg(short = 'l', long = "language", value_enum, default_value_t)]
+        language: LanguageChoice,
+        /// Passphrase to use when deriving the BIP39 seed, sometimes called the "25th word"
+        #[arg(long = "passphrase", value_name = "PASSPHRASE", default_value = "")]
+        passphrase: String,
+    },
+    /// Derive the BIP32 master extended private key from a BIP39 mnemonic sentence read from stdin
+    ///
+    /// The mnemonic sentence is validated first, in the same way as with `validate`.
+    Xprv {
+        /// Language of the BIP39 mnemonic sentence
+        #[arg(short = 'l', long = "language", value_enum, default_value_t)]
+        language: LanguageChoice,
+        /// Passphrase to use when deriving the BIP39 seed, sometimes called the "25th word"
+        #[arg(long = "passphrase", value_name = "PASSPHRASE", default_value = "")]
+        passphrase: String,
+        /// Serialize for testnet (tprv) instead of mainnet (xprv)
+        #[arg(long = "testnet")]
+        testnet: bool,
+    },
+    /// Decode a BIP39 mnemonic sentence read from stdin back into the entropy (hex) it encodes
+    ///
+    /// The mnemonic sentence is validated first, in the same way as with `validate`.
+    Entropy {
+        /// Language of the BIP39 mnemonic sentence
+        #[arg(short = 'l', long = "language", value_enum, default_value_t)]
+        language: LanguageChoice,
+    },
+    /// Recover missing or illegible words of a BIP39 mnemonic sentence read from stdin
+    ///
+    /// Write `?` in place of each unknown word, at most two of them. All mnemonic sentences
+    /// with a valid checksum are printed to stdout, one per line.
+    Recover {
+        /// Language of the BIP39 mnemonic sentence
+        #[arg(short = 'l', long = "language", value_enum, default_value_t)]
+        language: LanguageChoice,
+    },
+    /// Check the quality of a built-in wordlist, or of a wordlist file
+    ///
+    /// Reports the number of words, the entropy per word, duplicate words, word lengths,
+    /// the unique prefix length, the minimum edit distance between any two words,
+    /// and words that are prefixes of other words.
+    WordlistCheck {
+        /// Select wordlist to check
+        #[arg(short = 'w', long = "wordlist", value_enum, default_value_t)]
+        use_wlist: WordlistChoice,
+        /// Select language of BIP39 wordlist to check. Requires BIP39 wordlist
+        #[arg(short = 'l', long = "language", value_enum, default_value_t)]
+        language: LanguageChoice,
+        /// Check wordlist from file instead of one of the built-in wordlists
+        ///
+        /// Exits with a non-zero exit code if the wordlist can not be used with `--wordlist-file`.
+        #[arg(long = "wordlist-file", value_name = "PATH", conflicts_with_all = ["use_wlist", "language"])]
+        wordlist_file: Option<PathBuf>,
+    },
+}
+
+/// Wordlists to choose from. The EFF wordlists are only available when their cargo features
+/// are enabled, while the BIP39 wordlist is always available.
 #[derive(Eq, PartialEq, Copy, Clone, Debug, ValueEnum)]
 enum WordlistChoice {
     /// EFF's Short Wordlist #2
@@ -57,6 +276,7 @@ enum WordlistChoice {
     /// Details:
     /// - <https://www.eff.org/deeplinks/2016/07/new-wordlists-random-passphrases>
     /// - <https://www.eff.org/dice>
+    #[cfg(feature = "eff-autocomplete")]
     EffAutocomplete,
     /// EFF's Long Wordlist
     ///
@@ -71,6 +291,7 @@ enum WordlistChoice {
     /// Details:
     /// - <https://www.eff.org/deeplinks/2016/07/new-wordlists-random-passphrases>
     /// - <https://www.eff.org/dice>
+    #[cfg(feature = "eff-long")]
     EffLong,
     /// EFF's Short Wordlist #1
     ///
@@ -80,6 +301,7 @@ enum WordlistChoice {
     /// Details:
     /// - <https://www.eff.org/deeplinks/2016/07/new-wordlists-random-passphrases>
     /// - <https://www.eff.org/dice>
+    #[cfg(feature = "eff-short")]
     EffShort,
     /// BIP39 wordlist
     ///
@@ -90,8 +312,243 @@ enum WordlistChoice {
 }
 
 impl Default for WordlistChoice {
+    /// The first of the wordlists that is enabled, which is EFF's Short Wordlist #2 by default.
     fn default() -> Self {
-        Self::EffAutocomplete
+        // The BIP39 wordlist is always available, so there is at least one variant.
+        Self::value_variants()[0]
+    }
+}
+
+impl WordlistChoice {
+    fn wordlist(self) -> &'static dyn Wordlist {
+        match self {
+            #[cfg(feature = "eff-autocomplete")]
+            Self::EffAutocomplete => &eff_lexical_data::WORDLIST_AUTOCOMPLETE,
+            #[cfg(feature = "eff-long")]
+            Self::EffLong => &eff_lexical_data::WORDLIST_LONG,
+            #[cfg(feature = "eff-short")]
+            Self::EffShort => &eff_lexical_data::WORDLIST_SHORT,
+            Self::Bip39 => &WORDLIST_BIP39,
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default, ValueEnum)]
+enum LanguageChoice {
+    #[default]
+    English,
+    #[cfg(feature = "bip39-cs")]
+    Czech,
+    #[cfg(feature = "bip39-es")]
+    Spanish,
+    #[cfg(feature = "bip39-fr")]
+    French,
+    #[cfg(feature = "bip39-it")]
+    Italian,
+    #[cfg(feature = "bip39-ja")]
+    /// Words are separated by ideographic space (U+3000)
+    Japanese,
+    #[cfg(feature = "bip39-ko")]
+    Korean,
+    #[cfg(feature = "bip39-pt")]
+    Portuguese,
+    #[cfg(feature = "bip39-zh-hans")]
+    ChineseSimplified,
+    #[cfg(feature = "bip39-zh-hant")]
+    ChineseTraditional,
+}
+
+impl LanguageChoice {
+    fn bip39_wordlist(self) -> &'static dyn Wordlist {
+        match self {
+            Self::English => &WORDLIST_BIP39,
+            #[cfg(feature = "bip39-cs")]
+            Self::Czech => &bip39_lexical_data::WORDLIST_BIP39_CS,
+            #[cfg(feature = "bip39-es")]
+            Self::Spanish => &bip39_lexical_data::WORDLIST_BIP39_ES,
+            #[cfg(feature = "bip39-fr")]
+            Self::French => &bip39_lexical_data::WORDLIST_BIP39_FR,
+            #[cfg(feature = "bip39-it")]
+            Self::Italian => &bip39_lexical_data::WORDLIST_BIP39_IT,
+            #[cfg(feature = "bip39-ja")]
+            Self::Japanese => &bip39_lexical_data::WORDLIST_BIP39_JA,
+            #[cfg(feature = "bip39-ko")]
+            Self::Korean => &bip39_lexical_data::WORDLIST_BIP39_KO,
+            #[cfg(feature = "bip39-pt")]
+            Self::Portuguese => &bip39_lexical_data::WORDLIST_BIP39_PT,
+            #[cfg(feature = "bip39-zh-hans")]
+            Self::ChineseSimplified => &bip39_lexical_data::WORDLIST_BIP39_ZH_HANS,
+            #[cfg(feature = "bip39-zh-hant")]
+            Self::ChineseTraditional => &bip39_lexical_data::WORDLIST_BIP39_ZH_HANT,
+        }
+    }
+}
+
+impl From<LanguageChoice> for Language {
+    fn from(language: LanguageChoice) -> Self {
+        match language {
+            LanguageChoice::English => Self::English,
+            #[cfg(feature = "bip39-cs")]
+            LanguageChoice::Czech => Self::Czech,
+            #[cfg(feature = "bip39-es")]
+            LanguageChoice::Spanish => Self::Spanish,
+            #[cfg(feature = "bip39-fr")]
+            LanguageChoice::French => Self::French,
+            #[cfg(feature = "bip39-it")]
+            LanguageChoice::Italian => Self::Italian,
+            #[cfg(feature = "bip39-ja")]
+            LanguageChoice::Japanese => Self::Japanese,
+            #[cfg(feature = "bip39-ko")]
+            LanguageChoice::Korean => Self::Korean,
+            #[cfg(feature = "bip39-pt")]
+            LanguageChoice::Portuguese => Self::Portuguese,
+            #[cfg(feature = "bip39-zh-hans")]
+            LanguageChoice::ChineseSimplified => Self::ChineseSimplified,
+            #[cfg(feature = "bip39-zh-hant")]
+            LanguageChoice::ChineseTraditional => Self::ChineseTraditional,
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default, ValueEnum)]
+enum SeparatorChoice {
+    /// A space
+    #[default]
+    Space,
+    /// A hyphen (-)
+    Hyphen,
+    /// A dot (.)
+    Dot,
+    /// Nothing, the words are put right after each other
+    None,
+    /// A digit (0-9) picked at random, different for each place between two words
+    Digit,
+    /// A symbol picked at random, different for each place between two words
+    Symbol,
+}
+
+impl SeparatorChoice {
+    fn separator(self) -> Separator {
+        match self {
+            Self::Space => " ".into(),
+            Self::Hyphen => "-".into(),
+            Self::Dot => ".".into(),
+            Self::None => "".into(),
+            Self::Digit => Separator::RandomDigit,
+            Self::Symbol => Separator::RandomSymbol,
+        }
+    }
+
+    fn is_random(self) -> bool {
+        matches!(self, Self::Digit | Self::Symbol)
+    }
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default, ValueEnum)]
+enum CapitalizationChoice {
+    /// Words are used as they are in the wordlist
+    #[default]
+    None,
+    /// The first letter of each word is uppercase
+    Title,
+    /// All letters are uppercase
+    Upper,
+    /// The first letter of each word except the first word is uppercase
+    Camel,
+    /// The first letter of one word, picked at random, is uppercase
+    OneRandom,
+}
+
+impl From<CapitalizationChoice> for Capitalization {
+    fn from(capitalization: CapitalizationChoice) -> Self {
+        match capitalization {
+            CapitalizationChoice::None => Self::Unchanged,
+            CapitalizationChoice::Title => Self::Title,
+            CapitalizationChoice::Upper => Self::Upper,
+            CapitalizationChoice::Camel => Self::Camel,
+            CapitalizationChoice::OneRandom => Self::OneRandomWord,
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default, ValueEnum)]
+enum OutputFormat {
+    /// Each passphrase on a line of its own
+    #[default]
+    Text,
+    /// A JSON array, with an object for each passphrase holding its words, the indexes
+    /// of the words in the wordlist, the name of the wordlist and the entropy in bits.
+    /// With the BIP39 wordlist, the entropy (hex) and the checksum bits are included as well
+    Json,
+    /// The same objects as with `json`, one per line (JSON Lines)
+    Jsonl,
+}
+
+/// A generated passphrase, along with the details that are included in the JSON output formats.
+#[derive(Serialize)]
+struct PassphraseOutput<'a> {
+    passphrase: String,
+    words: Vec<&'a str>,
+    indices: Vec<usize>,
+    wordlist: &'a str,
+    entropy_bits: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entropy_hex: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum_bits: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bip39_seed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bip32_xprv: Option<String>,
+}
+
+/// Writes generated passphrases in the selected output format.
+///
+/// With `json`, the passphrases are collected and written as one array by [`Self::finish`],
+/// while with the other formats each passphrase is written as soon as it has been generated.
+struct PassphraseWriter<'a, W: Write> {
+    handle: W,
+    format: OutputFormat,
+    collected: Vec<PassphraseOutput<'a>>,
+}
+
+impl<'a, W: Write> PassphraseWriter<'a, W> {
+    fn new(handle: W, format: OutputFormat) -> Self {
+        Self {
+            handle,
+            format,
+            collected: vec![],
+        }
+    }
+
+    fn write(&mut self, output: PassphraseOutput<'a>) -> anyhow::Result<()> {
+        match self.format {
+            OutputFormat::Text => {
+                self.handle.write_all(output.passphrase.as_bytes())?;
+                for line in [&output.bip39_seed, &output.bip32_xprv]
+                    .into_iter()
+                    .flatten()
+                {
+                    self.handle.write_all(b"\n")?;
+                    self.handle.write_all(line.as_bytes())?;
+                }
+                self.handle.write_all(b"\n")?;
+            }
+            OutputFormat::Json => self.collected.push(output),
+            OutputFormat::Jsonl => {
+                serde_json::to_writer(&mut self.handle, &output)?;
+                self.handle.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> anyhow::Result<()> {
+        if self.format == OutputFormat::Json {
+            serde_json::to_writer_pretty(&mut self.handle, &self.collected)?;
+            self.handle.write_all(b"\n")?;
+        }
+        Ok(())
     }
 }
 
@@ -99,122 +556,746 @@ impl Default for WordlistChoice {
 enum Error {
     #[error("Invalid number of words for BIP39: {0}")]
     Bip39MSLenInvalid(usize),
+    #[error("Input ended before all of the dice had been entered")]
+    DiceInputEnded,
+    #[error("Option {0} can only be used with the BIP39 wordlist")]
+    RequiresBip39Wordlist(&'static str),
+    #[error("The BIP39 seed is derived from the mnemonic sentence exactly as given, so it must be entered in lowercase")]
+    MnemonicNotLowercase,
+    #[error("Invalid hex string: {0:?}")]
+    InvalidHex(String),
+    #[error("Invalid number of bytes of entropy for BIP39: {0}")]
+    Bip39EntLenInvalid(usize),
+    #[error("No BIP39 mnemonic sentence has {0} bits of entropy or more")]
+    Bip39BitsUnsatisfiable(u32),
+    #[error("No passphrase within the length limits has {0} bits of entropy or more")]
+    BitsUnsatisfiableWithinLength(u32),
+    #[error("Option {0} can not be used with the BIP39 wordlist")]
+    NotWithBip39Wordlist(&'static str),
+    #[error("Option {0} picks at random, so it can not be used with dice")]
+    RandomWithDice(&'static str),
+    #[error("A password policy can not be used with the BIP39 wordlist")]
+    PolicyNotWithBip39Wordlist,
+    #[error("A password policy can not be used with dice")]
+    PolicyWithDice,
+    #[error("Option {0} can not be used with dice")]
+    NotWithDice(&'static str),
+    #[error("Wordlist can not be used with dice: {0}")]
+    NotDiceWordlist(String),
+    #[error("No mnemonic sentence with a valid checksum could be recovered")]
+    NothingRecovered,
 }
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
-    let wordlist = match cli.use_wlist {
-        WordlistChoice::EffAutocomplete => WL_AUTOCOMPLETE,
-        WordlistChoice::EffLong => WL_LONG,
-        WordlistChoice::EffShort => WL_SHORT,
-        WordlistChoice::Bip39 => WL_BIP39,
+    match cli.command {
+        Some(Command::Validate { language }) => return validate_mnemonic(language.into()),
+        Some(Command::Seed {
+            language,
+            passphrase,
+        }) => return derive_seed(language.into(), &passphrase),
+        Some(Command::Xprv {
+            language,
+            passphrase,
+            testnet,
+        }) => return derive_xprv(language.into(), &passphrase, network(testnet)),
+        Some(Command::Entropy { language }) => return decode_entropy(language.into()),
+        Some(Command::Recover { language }) => return recover_words(language.into()),
+        Some(Command::WordlistCheck {
+            use_wlist,
+            language,
+            wordlist_file,
+        }) => return check_wordlist(use_wlist, language, wordlist_file.as_deref()),
+        None => {}
+    }
+
+    let stdout = stdout();
+    let handle = stdout.lock();
+
+    match cli.seed {
+        Some(seed) => {
+            eprintln!("WARNING: --seed makes the output predictable. The generated passphrases are NOT secure and must only be used for testing.");
+            generate(&cli, ChaCha20Rng::seed_from_u64(seed), handle)
+        }
+        None => generate(&cli, thread_rng(), handle),
+    }
+}
+
+/// Generate passphrases according to the given settings, and write them to `handle`.
+fn generate<R: RngCore + CryptoRng>(
+    cli: &Cli,
+    mut rng: R,
+    mut handle: impl Write,
+) -> anyhow::Result<()> {
+    // A wordlist file is used instead of the wordlist chosen with `-w`, even if that happens
+    // to be the BIP39 wordlist, which is the default when no EFF wordlists are built in.
+    let use_bip39 = cli.wordlist_file.is_none() && cli.use_wlist == WordlistChoice::Bip39;
+
+    if cli.language != LanguageChoice::English && !use_bip39 {
+        return Err(Error::RequiresBip39Wordlist("--language").into());
+    }
+    if cli.print_bip39_seed && !use_bip39 {
+        return Err(Error::RequiresBip39Wordlist("--bip39-seed").into());
+    }
+    if cli.print_bip32_xprv && !use_bip39 {
+        return Err(Error::RequiresBip39Wordlist("--bip32-xprv").into());
+    }
+    // A BIP39 mnemonic sentence must use the words exactly as they are in the wordlist.
+    if cli.separator != SeparatorChoice::default() && use_bip39 {
+        return Err(Error::NotWithBip39Wordlist("--separator").into());
+    }
+    if cli.capitalization != CapitalizationChoice::default() && use_bip39 {
+        return Err(Error::NotWithBip39Wordlist("--capitalize").into());
+    }
+    if cli.use_physical_dice && cli.separator.is_random() {
+        return Err(Error::RandomWithDice("--separator").into());
+    }
+    if cli.use_physical_dice && cli.capitalization == CapitalizationChoice::OneRandom {
+        return Err(Error::RandomWithDice("--capitalize").into());
+    }
+    // Dice pick from the whole wordlist, so they can not skip the words that are too long.
+    if cli.max_word_length.is_some() && use_bip39 {
+        return Err(Error::NotWithBip39Wordlist("--max-word-length").into());
+    }
+    if cli.max_word_length.is_some() && cli.use_physical_dice {
+        return Err(Error::NotWithDice("--max-word-length").into());
+    }
+
+    let policy = read_policy(cli)?;
+    if !policy.is_empty() && use_bip39 {
+        return Err(Error::PolicyNotWithBip39Wordlist.into());
+    }
+    if !policy.is_empty() && cli.use_physical_dice {
+        return Err(Error::PolicyWithDice.into());
+    }
+
+    let supplied_ent = match &cli.entropy_hex {
+        Some(hex) => {
+            if !use_bip39 {
+                return Err(Error::RequiresBip39Wordlist("--entropy-hex").into());
+            }
+            let ent = from_hex(hex)?;
+            if ms_len_for_ent_num_bytes(ent.len()).is_none() {
+                eprintln!("Entropy for BIP39 must be one of 16, 20, 24, 28 or 32 bytes (32, 40, 48, 56 or 64 hex digits)");
+                return Err(Error::Bip39EntLenInvalid(ent.len()).into());
+            }
+            Some(ent)
+        }
+        None => None,
     };
 
-    // the EFF wordlists have lengths that are an exact power of 6,
-    // whereas the bip39 wordlist does not
-    let num_dice: u32 = if cli.use_wlist == WordlistChoice::Bip39 {
-        // bip39 has 2048 words, which is a power of 2.
-        // we need 11 dice because 6**11 / 3**11 = 2048,
-        // i.e. we use 11 dice because it leads to a multiple
-        // of the wordlist length
-        11
-    } else if cli.use_wlist == WordlistChoice::EffLong {
-        // EFF long wordlist has 6**5 = 7776 words
-        5
-    } else {
-        // Other EFF wordlists have 6**4 = 1296 words
-        4
+    let wordlist_text;
+    let loaded_wordlist;
+    let wordlist: &dyn Wordlist = match &cli.wordlist_file {
+        Some(path) => {
+            wordlist_text = read_wordlist_file(path)?;
+            loaded_wordlist = LoadedWordlist::parse(&path.display().to_string(), &wordlist_text)?;
+            &loaded_wordlist
+        }
+        None => cli.use_wlist.wordlist(),
     };
+    if cli.use_physical_dice && wordlist.dice_digits().is_none() && !use_bip39 {
+        eprintln!("To use dice, each line of the wordlist file must start with the dice digits of the word, followed by a tab.");
+        return Err(Error::NotDiceWordlist(wordlist.name().to_string()).into());
+    }
 
     let num_passphrases = cli.k;
 
-    let num_words = match cli.n {
-        Some(n) => {
-            // BIP39 has specific allowable lengths of the generated mnemonic sentence (MS) in words.
-            // See <https://en.bitcoin.it/wiki/BIP_0039#Generating_the_mnemonic> for details.
-            let bip39_allowable_mnemonic_sentence_lengths: [usize; 5] = [12, 15, 18, 21, 24];
-            if cli.use_wlist == WordlistChoice::Bip39
-                && !bip39_allowable_mnemonic_sentence_lengths.contains(&n)
-            {
-                eprintln!("When BIP39 wordlist is used, number of words to use must be one of: {bip39_allowable_mnemonic_sentence_lengths:?}");
-                return Err(Error::Bip39MSLenInvalid(n).into());
-            }
-            n
+    let generator_builder = |num_words| {
+        let builder = PassphraseGenerator::builder(wordlist.words())
+            .num_words(num_words)
+            .separator(cli.separator.separator())
+            .capitalization(cli.capitalization.into())
+            .policy(policy.clone());
+        match cli.max_word_length {
+            Some(max_word_length) => builder.max_word_length(max_word_length),
+            None => builder,
         }
-        None => {
-            if cli.use_wlist == WordlistChoice::EffLong {
-                10
-            } else {
-                12
+    };
+
+    let mut num_words = if let Some(ent) = &supplied_ent {
+        // The length of supplied entropy has already been validated above, so unwrap is fine here.
+        ms_len_for_ent_num_bytes(ent.len()).unwrap()
+    } else {
+        match cli.n {
+            Some(n) => {
+                // BIP39 has specific allowable lengths of the generated mnemonic sentence (MS) in words.
+                if use_bip39 && !ALLOWABLE_MS_LENGTHS.contains(&n) {
+                    eprintln!("When BIP39 wordlist is used, number of words to use must be one of: {ALLOWABLE_MS_LENGTHS:?}");
+                    return Err(Error::Bip39MSLenInvalid(n).into());
+                }
+                n
             }
+            None => match cli.bits {
+                Some(bits) if use_bip39 => ms_len_for_entropy_bits(bits).ok_or_else(|| {
+                    eprintln!("When BIP39 wordlist is used, number of bits must be at most 256");
+                    Error::Bip39BitsUnsatisfiable(bits)
+                })?,
+                Some(bits) => num_words_for_entropy_bits(bits, wordlist.entropy_bits_per_word()),
+                None => wordlist.default_num_words(),
+            },
         }
     };
 
-    let stdout = stdout();
-    let mut handle = stdout.lock();
+    if let Some(bits) = cli
+        .bits
+        .filter(|_| !use_bip39 && (!policy.is_empty() || cli.max_word_length.is_some()))
+    {
+        // Leaving out words that are too long, and picking passphrases again when they are not
+        // within the length limits of the policy, lowers the entropy, so add words until there
+        // is enough.
+        loop {
+            match generator_builder(num_words).build() {
+                Ok(generator) if generator.entropy_bits() >= f64::from(bits) => break,
+                Ok(_) => {}
+                // More words may still reach a minimum length, but a passphrase can not have
+                // more words than it has characters, so stop there when there is a maximum length.
+                Err(passphrase::Error::LengthUnsatisfiable | passphrase::Error::LengthUnlikely)
+                    if policy.max_length.is_some_and(|max| num_words >= max) =>
+                {
+                    eprintln!("The number of words needed for {bits} bits of entropy does not fit within the maximum length. Increase --max-length or --max-word-length, or lower --bits.");
+                    return Err(Error::BitsUnsatisfiableWithinLength(bits).into());
+                }
+                Err(passphrase::Error::LengthUnsatisfiable | passphrase::Error::LengthUnlikely) => {
+                }
+                Err(err) => return Err(err.into()),
+            }
+            num_words += 1;
+        }
+    }
 
     if cli.calculate_entropy {
+        let mut details = vec![];
+        let (entropy_bits, average_length) = if use_bip39 {
+            // The entropy of a BIP39 mnemonic sentence is the number of random bits (ENT)
+            // it was generated from. The checksum bits do not add any entropy.
+            // The number of words has already been validated above, so unwrap is fine here.
+            let ent_num_bits = ent_num_bytes_for_ms_len(num_words).unwrap() * 8;
+            let bip39_wordlist = cli.language.bip39_wordlist();
+            details.push(("Wordlist:", bip39_wordlist.name().to_string()));
+            details.push((
+                "Words:",
+                format!(
+                    "{num_words}, each encoding 11 bits, {} bits in total",
+                    num_words * 11
+                ),
+            ));
+            details.push(("Entropy (ENT):", format!("{ent_num_bits} bits")));
+            details.push((
+                "Checksum (CS):",
+                format!(
+                    "{} bits, calculated from ENT, so they add no entropy",
+                    ent_num_bits / 32
+                ),
+            ));
+            let separator_len = Language::from(cli.language).separator().chars().count();
+            let average_length = num_words as f64 * average_word_len(bip39_wordlist.words())
+                + ((num_words - 1) * separator_len) as f64;
+            (ent_num_bits as f64, average_length)
+        } else {
+            let generator = generator_builder(num_words).build()?;
+            let breakdown = generator.entropy_breakdown();
+            details.push(("Wordlist:", wordlist.name().to_string()));
+            details.push((
+                "Words:",
+                format!(
+                    "{num_words}, each picked from {} words",
+                    breakdown.num_candidates
+                ),
+            ));
+            details.push((
+                "Entropy per word:",
+                format!("{:.2} bits", breakdown.bits_per_word),
+            ));
+            details.push((
+                "Entropy of words:",
+                format!("{:.2} bits", breakdown.words_bits()),
+            ));
+            for (label, bits) in [
+                ("Length limits:", breakdown.length_limit_bits),
+                ("Random separators:", breakdown.separator_bits),
+                ("Random capitalization:", breakdown.capitalization_bits),
+                ("Inserted characters:", breakdown.insertion_bits),
+            ] {
+                if bits != 0.0 {
+                    details.push((label, format!("{bits:+.2} bits")));
+                }
+            }
+            (breakdown.total_bits(), generator.average_length())
+        };
+
         handle.write_fmt(format_args!(
-            "Current settings will create passphrases with {:.2} bits of entropy.\n",
-            (num_words as f64) * (wordlist.len() as f64).log2()
+            "Current settings will create passphrases with {entropy_bits:.2} bits of entropy.\n",
         ))?;
+        writeln!(handle)?;
+        for (label, value) in details {
+            writeln!(handle, "{label:<25}{value}")?;
+        }
+        writeln!(handle, "{:<25}{entropy_bits:.2} bits", "Total entropy:")?;
+        writeln!(
+            handle,
+            "{:<25}2^{entropy_bits:.2} ~= {:.2e}",
+            "Possible passphrases:",
+            entropy_bits.exp2()
+        )?;
+        writeln!(
+            handle,
+            "{:<25}{average_length:.2} characters",
+            "Average length:"
+        )?;
+        let guess_rates = if cli.guess_rates.is_empty() {
+            &DEFAULT_GUESS_RATES[..]
+        } else {
+            &cli.guess_rates[..]
+        };
+        for (i, rate) in guess_rates.iter().enumerate() {
+            // On average, half of the possible passphrases have to be tried before finding it.
+            let seconds = (entropy_bits - 1.0).exp2() / rate;
+            let label = if i == 0 { "Average time to crack:" } else { "" };
+            writeln!(
+                handle,
+                "{label:<25}{} at {rate} guesses per second",
+                format_duration(seconds)
+            )?;
+        }
+    } else if use_bip39 {
+        let mut writer = PassphraseWriter::new(handle, cli.format);
+        let language = Language::from(cli.language);
+        let wordlist_name = cli.language.bip39_wordlist().name();
+        for _ in 0..num_passphrases {
+            // The number of words has already been validated above, so unwrap is fine here.
+            let ent_num_bytes = ent_num_bytes_for_ms_len(num_words).unwrap();
+            let ent = if let Some(ent) = &supplied_ent {
+                ent.clone()
+            } else if cli.use_physical_dice {
+                read_dice_entropy(&mut stdin().lock(), ent_num_bytes)?
+            } else {
+                let mut ent = vec![0u8; ent_num_bytes];
+                rng.fill_bytes(&mut ent);
+                ent
+            };
+
+            let words = mnemonic_from_entropy(&ent, language)?;
+            let (cs, num_cs_bits) = checksum(&ent)?;
+
+            let seed = (cli.print_bip39_seed || cli.print_bip32_xprv)
+                .then(|| seed_from_mnemonic(&words, &cli.bip39_passphrase));
+            let bip32_xprv = match seed.filter(|_| cli.print_bip32_xprv) {
+                Some(seed) => {
+                    Some(MasterKey::from_seed(&seed)?.to_base58check(network(cli.testnet)))
+                }
+                None => None,
+            };
+
+            writer.write(PassphraseOutput {
+                passphrase: words.join(language.separator()),
+                indices: word_indices_from_entropy(&ent)?
+                    .into_iter()
+                    .map(usize::from)
+                    .collect(),
+                words,
+                wordlist: wordlist_name,
+                entropy_bits: (ent.len() * 8) as f64,
+                entropy_hex: Some(to_hex(&ent)),
+                checksum_bits: Some(format!("{cs:0num_cs_bits$b}")),
+                bip39_seed: seed
+                    .filter(|_| cli.print_bip39_seed)
+                    .map(|seed| to_hex(&seed)),
+                bip32_xprv,
+            })?;
+        }
+        writer.finish()?;
     } else {
+        let mut generator = generator_builder(num_words).rng(rng).build()?;
+        let mut writer = PassphraseWriter::new(handle, cli.format);
+
         for _ in 0..num_passphrases {
-            if cli.use_physical_dice {
+            let passphrase = if cli.use_physical_dice {
+                // The EFF wordlists have lengths that are an exact power of 6,
+                // so that each word can be picked with a fixed number of dice.
+                // With the BIP39 wordlist, dice are instead used to collect entropy bits,
+                // see `read_dice_entropy`.
+                // Wordlists without dice digits have already been rejected above, so unwrap is fine here.
+                let num_dice = wordlist.dice_digits().unwrap();
+
                 let mut word_idx = vec![0usize; num_words];
 
                 let width = format!("{num_words}").len();
 
                 for (i, item) in word_idx.iter_mut().enumerate().take(num_words) {
                     eprint!("Word {:>w$} / {}. ", i + 1, num_words, w = width);
-                    // For the sake of the bip39 wordlist, we modulo index by the wordlist length,
-                    // because the range of the possible values is a multiple of the wordlist length.
-                    //
                     // With the EFF wordlists, the wordlist lengths match the range
-                    // of the numbers we get from the dice, so for EFF wordlists
-                    // this modulo does not change anything.
-                    *item = read_dice(num_dice) % wordlist.len();
-                }
-
-                for i in 0..num_words {
-                    handle.write_all(wordlist[word_idx[i]].as_bytes())?;
-                    if i < (num_words - 1) {
-                        handle.write_all(b" ")?;
+                    // of the numbers we get from the dice.
+                    *item = read_dice(&mut stdin().lock(), num_dice)?;
+                    // Show the word picked by the dice, so that it can be checked
+                    // against a printed copy of the wordlist.
+                    let word = wordlist.words()[*item];
+                    match DiceCode::from_index(*item, num_dice) {
+                        Some(code) => eprintln!("{code} {word}"),
+                        None => eprintln!("{word}"),
                     }
                 }
+
+                generator.format(word_idx)
             } else {
-                let mut rng = thread_rng();
-
-                for i in 0..num_words {
-                    handle.write_all(
-                        wordlist[rng.gen_range(0..wordlist.len()) as usize].as_bytes(),
-                    )?;
-                    if i < (num_words - 1) {
-                        handle.write_all(b" ")?;
-                    }
-                }
-            }
+                generator.generate()
+            };
+
+            writer.write(PassphraseOutput {
+                passphrase: passphrase.to_string(),
+                words: passphrase.words().to_vec(),
+                indices: passphrase.indices().to_vec(),
+                wordlist: wordlist.name(),
+                entropy_bits: passphrase.entropy_bits(),
+                entropy_hex: None,
+                checksum_bits: None,
+                bip39_seed: None,
+                bip32_xprv: None,
+            })?;
+        }
+        writer.finish()?;
+    }
+
+    Ok(())
+}
+
+/// Read a BIP39 mnemonic sentence from stdin, and validate it. See [`parse_mnemonic`].
+fn read_mnemonic(language: Language) -> anyhow::Result<Vec<String>> {
+    parse_mnemonic(&read_to_string(stdin())?, language)
+}
+
+/// Parse and validate a BIP39 mnemonic sentence.
+///
+/// The words are looked up in the wordlist in lowercase, so that a mnemonic sentence written
+/// with capital letters is still recognized, but the words are returned exactly as given.
+///
+/// Any problems with the mnemonic sentence are reported to stderr before returning an error.
+fn parse_mnemonic(input: &str, language: Language) -> anyhow::Result<Vec<String>> {
+    let words = input.split_whitespace().collect::<Vec<_>>();
+    let lowercase_words = words
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>();
+    let lowercase_words = lowercase_words
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    if !ALLOWABLE_MS_LENGTHS.contains(&words.len()) {
+        eprintln!(
+            "Number of words in a BIP39 mnemonic sentence must be one of: {ALLOWABLE_MS_LENGTHS:?}"
+        );
+    }
+    // Report all unknown words, not just the first one, so that they can all be fixed at once.
+    for (i, word) in lowercase_words.iter().enumerate() {
+        if get_11_bits_from_word(word, language).is_none() {
+            eprintln!(
+                "Word number {} is not in the BIP39 wordlist: {word:?}",
+                i + 1
+            );
+        }
+    }
+
+    entropy_from_mnemonic(&lowercase_words, language)?;
+
+    Ok(words.into_iter().map(String::from).collect())
+}
+
+/// Derive the BIP39 seed from a validated mnemonic sentence.
+///
+/// BIP39 derives the seed from the mnemonic sentence exactly as given, only normalized to NFKD,
+/// so the same words with capital letters would give a different seed than in lowercase.
+/// Rather than silently deriving such a seed, mnemonic sentences with capital letters are rejected.
+fn mnemonic_seed(words: &[String], passphrase: &str) -> anyhow::Result<[u8; 64]> {
+    if words.iter().any(|word| *word != word.to_lowercase()) {
+        return Err(Error::MnemonicNotLowercase.into());
+    }
+    let words = words.iter().map(String::as_str).collect::<Vec<_>>();
+    Ok(seed_from_mnemonic(&words, passphrase))
+}
+
+fn validate_mnemonic(language: Language) -> anyhow::Result<()> {
+    let words = read_mnemonic(language)?;
+
+    let stdout = stdout();
+    let mut handle = stdout.lock();
+    handle.write_fmt(format_args!(
+        "Valid BIP39 mnemonic sentence of {} words.\n",
+        words.len()
+    ))?;
+
+    Ok(())
+}
+
+fn derive_seed(language: Language, passphrase: &str) -> anyhow::Result<()> {
+    let seed = mnemonic_seed(&read_mnemonic(language)?, passphrase)?;
+
+    let stdout = stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(to_hex(&seed).as_bytes())?;
+    handle.write_all(b"\n")?;
+
+    Ok(())
+}
+
+fn derive_xprv(language: Language, passphrase: &str, network: Network) -> anyhow::Result<()> {
+    let seed = mnemonic_seed(&read_mnemonic(language)?, passphrase)?;
+    let master_key = MasterKey::from_seed(&seed)?;
+
+    let stdout = stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(master_key.to_base58check(network).as_bytes())?;
+    handle.write_all(b"\n")?;
+
+    Ok(())
+}
+
+fn decode_entropy(language: Language) -> anyhow::Result<()> {
+    let words = read_mnemonic(language)?;
+    let words = words.iter().map(String::as_str).collect::<Vec<_>>();
+    let ent = entropy_from_mnemonic(&words, language)?;
 
-            handle.write_all(b"\n")?;
+    let stdout = stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(to_hex(&ent).as_bytes())?;
+    handle.write_all(b"\n")?;
+
+    Ok(())
+}
+
+fn recover_words(language: Language) -> anyhow::Result<()> {
+    let input = read_to_string(stdin())?.to_lowercase();
+    let words = input.split_whitespace().collect::<Vec<_>>();
+
+    if !ALLOWABLE_MS_LENGTHS.contains(&words.len()) {
+        eprintln!(
+            "Number of words in a BIP39 mnemonic sentence must be one of: {ALLOWABLE_MS_LENGTHS:?}"
+        );
+    }
+    // Only words that are neither placeholders nor in the wordlist are reported here.
+    for (i, word) in words.iter().enumerate() {
+        if *word != UNKNOWN_WORD_PLACEHOLDER && get_11_bits_from_word(word, language).is_none() {
+            eprintln!(
+                "Word number {} is not in the BIP39 wordlist: {word:?}",
+                i + 1
+            );
         }
     }
 
+    let candidates = recover_mnemonic(&words, language)?;
+    eprintln!(
+        "Found {} mnemonic sentence(s) with a valid checksum.",
+        candidates.len()
+    );
+    if candidates.is_empty() {
+        return Err(Error::NothingRecovered.into());
+    }
+
+    let stdout = stdout();
+    let mut handle = stdout.lock();
+    for candidate in candidates {
+        handle.write_all(candidate.join(language.separator()).as_bytes())?;
+        handle.write_all(b"\n")?;
+    }
+
     Ok(())
 }
 
-fn read_dice(n: u32) -> usize {
+fn read_wordlist_file(path: &Path) -> anyhow::Result<String> {
+    File::open(path)
+        .and_then(read_to_string)
+        .with_context(|| format!("Unable to read wordlist file {}", path.display()))
+}
+
+/// Get the password policy given with options, or read it from the policy file.
+fn read_policy(cli: &Cli) -> anyhow::Result<Policy> {
+    match &cli.policy_file {
+        Some(path) => {
+            let text = File::open(path)
+                .and_then(read_to_string)
+                .with_context(|| format!("Unable to read policy file {}", path.display()))?;
+            Policy::parse(&text).with_context(|| format!("Invalid policy file {}", path.display()))
+        }
+        None => Ok(Policy {
+            min_length: cli.min_length,
+            max_length: cli.max_length,
+            require_digit: cli.require_digit,
+            require_uppercase: cli.require_uppercase,
+            require_symbol: cli.require_symbol,
+        }),
+    }
+}
+
+fn check_wordlist(
+    use_wlist: WordlistChoice,
+    language: LanguageChoice,
+    wordlist_file: Option<&Path>,
+) -> anyhow::Result<()> {
+    if language != LanguageChoice::English && use_wlist != WordlistChoice::Bip39 {
+        return Err(Error::RequiresBip39Wordlist("--language").into());
+    }
+
+    let wordlist_text;
+    let (name, source, words, validation) = match wordlist_file {
+        Some(path) => {
+            wordlist_text = read_wordlist_file(path)?;
+            let name = path.display().to_string();
+            // Analyze the words even if the wordlist is not valid, so that all of the problems
+            // can be seen at once. The result of the validation is reported afterwards.
+            let validation = LoadedWordlist::parse(&name, &wordlist_text).map(|_| ());
+            (name, None, words_unchecked(&wordlist_text), validation)
+        }
+        None => {
+            let wordlist = if use_wlist == WordlistChoice::Bip39 {
+                language.bip39_wordlist()
+            } else {
+                use_wlist.wordlist()
+            };
+            (
+                wordlist.name().to_string(),
+                wordlist.source_url(),
+                wordlist.words().to_vec(),
+                Ok(()),
+            )
+        }
+    };
+
+    let analysis = analyze(&words);
+
+    let stdout = stdout();
+    let mut handle = stdout.lock();
+    writeln!(handle, "Wordlist:                {name}")?;
+    if let Some(source) = source {
+        writeln!(handle, "Source:                  {source}")?;
+    }
+    writeln!(handle, "Number of words:         {}", analysis.num_words)?;
+    writeln!(
+        handle,
+        "Entropy per word:        {:.2} bits",
+        analysis.entropy_bits_per_word
+    )?;
+    writeln!(
+        handle,
+        "Duplicate words:         {}",
+        analysis.duplicates.len()
+    )?;
+    writeln!(
+        handle,
+        "Word length:             {} shortest, {} longest, {:.2} average",
+        analysis.shortest_word_len, analysis.longest_word_len, analysis.average_word_len
+    )?;
+    writeln!(
+        handle,
+        "Unique prefix length:    {}",
+        analysis.unique_prefix_len
+    )?;
+    writeln!(
+        handle,
+        "Minimum edit distance:   {}",
+        analysis.min_edit_distance
+    )?;
+    writeln!(
+        handle,
+        "Prefixes of other words: {}",
+        analysis.prefix_words.len()
+    )?;
+    for word in &analysis.duplicates {
+        writeln!(handle, "Duplicate word: {word}")?;
+    }
+    for (prefix, word) in &analysis.prefix_words {
+        writeln!(handle, "Word {prefix:?} is a prefix of {word:?}")?;
+    }
+
+    validation?;
+
+    Ok(())
+}
+
+/// Guessing rates, in guesses per second, that the time to crack passphrases is estimated at
+/// when none are given with `--guess-rate`.
+const DEFAULT_GUESS_RATES: [f64; 2] = [1e4, 1e10];
+
+fn parse_guess_rate(rate: &str) -> Result<f64, String> {
+    match rate.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        _ => Err(format!("must be a positive number, not {rate:?}")),
+    }
+}
+
+/// Format a duration, given in seconds, in the largest unit that it is at least one of.
+fn format_duration(seconds: f64) -> String {
+    if seconds < 1.0 {
+        return "less than a second".to_string();
+    }
+    const UNITS: [(&str, f64); 4] = [
+        ("years", 365.25 * 24.0 * 60.0 * 60.0),
+        ("days", 24.0 * 60.0 * 60.0),
+        ("hours", 60.0 * 60.0),
+        ("minutes", 60.0),
+    ];
+    let (unit, amount) = UNITS
+        .iter()
+        .find(|&&(_, unit_seconds)| seconds >= unit_seconds)
+        .map_or(("seconds", seconds), |&(unit, unit_seconds)| {
+            (unit, seconds / unit_seconds)
+        });
+    if amount >= 1e6 {
+        format!("{amount:.2e} {unit}")
+    } else {
+        format!("{amount:.2} {unit}")
+    }
+}
+
+fn average_word_len(words: &[&str]) -> f64 {
+    let total_len = words.iter().map(|word| word.chars().count()).sum::<usize>();
+    total_len as f64 / words.len() as f64
+}
+
+fn network(testnet: bool) -> Network {
+    if testnet {
+        Network::Testnet
+    } else {
+        Network::Mainnet
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    let hex = hex.trim();
+    if !hex.len().is_multiple_of(2) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::InvalidHex(hex.to_string()));
+    }
+    Ok((0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect())
+}
+
+/// Read a line of dice throws from `input`, failing if there is no more input.
+fn read_dice_line(input: &mut impl BufRead) -> anyhow::Result<String> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        // Move past the prompt, which was not followed by any input.
+        eprintln!();
+        return Err(Error::DiceInputEnded.into());
+    }
+    Ok(line)
+}
+
+fn read_dice(input: &mut impl BufRead, n: u32) -> anyhow::Result<usize> {
     eprint!("Throw {n} dice and enter the number of eyes shown on each: ");
 
     let mut result = 0;
     let mut i = 0;
 
     while i < n {
-        let mut input = String::new();
-
-        stdin().read_line(&mut input).unwrap();
+        let line = read_dice_line(input)?;
 
-        for c in input.chars() {
+        for c in line.chars() {
             match c {
                 '1' | '2' | '3' | '4' | '5' | '6' => {
                     result += (c.to_digit(10).unwrap() - 1) * (6u32).pow(n - i - 1);
@@ -229,5 +1310,325 @@ fn read_dice(n: u32) -> usize {
         }
     }
 
-    result as usize
+    Ok(result as usize)
+}
+
+fn read_dice_entropy(input: &mut impl BufRead, ent_num_bytes: usize) -> anyhow::Result<Vec<u8>> {
+    let mut collector = DiceEntropyCollector::new(ent_num_bytes);
+
+    eprintln!(
+        "Collecting {} bits of entropy. Each die showing 1-4 eyes gives two bits, and each die showing 5-6 eyes gives one bit.",
+        collector.num_bits_remaining()
+    );
+
+    while !collector.is_complete() {
+        eprint!(
+            "{:>3} bits left. Throw some dice and enter the number of eyes shown on each: ",
+            collector.num_bits_remaining()
+        );
+
+        let line = read_dice_line(input)?;
+
+        for c in line.chars() {
+            match c {
+                '1' | '2' | '3' | '4' | '5' | '6' => {
+                    collector.add_throw(c.to_digit(10).unwrap() as u8)?;
+                }
+                _ => {}
+            }
+
+            if collector.is_complete() {
+                break;
+            }
+        }
+    }
+
+    // The loop above only exits once all bits have been collected, so unwrap is fine here.
+    Ok(collector.into_entropy().unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        format_duration, generate, mnemonic_seed, parse_mnemonic, read_dice, read_dice_entropy,
+        to_hex, Cli, Error,
+    };
+    use clap::Parser;
+    use pgen::bip39_algorithm::Language;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use test_case::test_case;
+
+    #[test_case("3 6\n1\n", Some(2 * 36 + 5 * 6); "with throws on several lines")]
+    #[test_case("3 6\n", None; "with input ending early")]
+    #[test_case("", None; "with no input")]
+    fn reads_dice(input: &str, expected: Option<usize>) {
+        let result = read_dice(&mut input.as_bytes(), 3);
+        match expected {
+            Some(expected) => assert_eq!(result.unwrap(), expected),
+            None => assert!(matches!(
+                result.unwrap_err().downcast_ref(),
+                Some(Error::DiceInputEnded)
+            )),
+        }
+    }
+
+    #[test]
+    fn reads_dice_entropy() {
+        // Each die showing 1-4 eyes gives two bits, so 64 such dice give 128 bits.
+        let input = "1 2 3 4 ".repeat(16);
+        assert_eq!(
+            read_dice_entropy(&mut input.as_bytes(), 16).unwrap(),
+            [0b00011011; 16]
+        );
+        let err = read_dice_entropy(&mut "1 2 3 4\n".as_bytes(), 16).unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(Error::DiceInputEnded)));
+    }
+
+    // First English test vector from <https://github.com/trezor/python-mnemonic/blob/b57a5ad77a981e743f4167ab2f7927a55c1e82a8/vectors.json>.
+    #[test]
+    fn derives_seed_from_lowercase_mnemonic() {
+        let words = parse_mnemonic("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about\n", Language::English).unwrap();
+        assert_eq!(
+            to_hex(&mnemonic_seed(&words, "TREZOR").unwrap()),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+    }
+
+    #[test]
+    fn rejects_seed_from_mixed_case_mnemonic() {
+        let input = "Abandon abandon ABANDON abandon abandon abandon abandon abandon abandon abandon abandon About";
+        // The words are still recognized when validating the mnemonic sentence.
+        let words = parse_mnemonic(input, Language::English).unwrap();
+        assert_eq!(words.join(" "), input);
+        let err = mnemonic_seed(&words, "TREZOR").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(Error::MnemonicNotLowercase)
+        ));
+    }
+
+    // Test cases that use the default wordlist are only run when it is EFF's Short Wordlist #2,
+    // and those that pick another wordlist are only run when that wordlist is enabled.
+
+    // Golden output. If any of these change, then the output of `pgen --seed` has changed,
+    // which will break the tests of scripts that rely on it.
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&[], "chute luau hugeness urologist gesture oftentimes equipment nylon singles double gaslight gadget\n"; "with default wordlist"))]
+    #[cfg_attr(feature = "eff-long", test_case(&["-w", "eff-long", "-k", "2"], "prepay common oozy illusive unlighted gnat province equation irritate pregnant\nelbow preteen overreact sprite disabled giggling dollar sway penpal grazing\n"; "with EFF long wordlist and two passphrases"))]
+    #[test_case(&["-w", "bip39"], "olive riot beef member alert sorry border black pause tumble enlist exile\n"; "with BIP39 wordlist")]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&["--separator", "digit", "--capitalize", "one-random"], "chute8luau1hugeness4urologist7gesture0oftentimes9equipment7nylon6singles3double1Gaslight5gadget\n"; "with random digit separator and one random capital"))]
+    #[cfg_attr(feature = "eff-long", test_case(&["-w", "eff-long", "-n", "4", "--separator", "hyphen", "--capitalize", "camel"], "prepay-Common-Oozy-Illusive\n"; "with hyphen separator and camel case"))]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&["-n", "5", "--separator", "symbol", "--capitalize", "upper"], "CHUTE:LUAU,HUGENESS.UROLOGIST:GESTURE\n"; "with random symbol separator and upper case"))]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&["-k", "3", "-n", "5", "--min-length", "16", "--max-length", "40", "--require-digit", "--require-uppercase", "--require-symbol"], "chute Qluau 6hugeness ^urologist gesture\npledge 5object Jyahoo boatyard %unknown\n2water osmosis actress sitcom jelly/I\n"; "with password policy"))]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&["-k", "2", "-n", "5", "--max-word-length", "5", "--max-length", "32"], "ozone dairy jelly wasp herbs\npeony focus john ozone nylon\n"; "with length limits"))]
+    #[test_case(&["-w", "bip39", "-n", "24", "--bip32-xprv"], "olive riot beef member alert sorry border black pause tumble enlist example there rural clean style void frog talk auction oxygen peasant faculty shield\nxprv9s21ZrQH143K4PV2EDxKj8ukVjKT6C52xXjr5E5s7pdpraF5FD9nq9dF7xo4GxvCSaeucLfuT39kzBC3K2jiF411NPNz8FNyjWLQwjJ6z8E\n"; "with BIP39 wordlist and BIP32 master extended private key")]
+    fn generates_same_output_with_same_seed(args: &[&str], expected: &str) {
+        let cli = Cli::try_parse_from(["pgen", "--seed", "1"].iter().chain(args)).unwrap();
+        let mut output = vec![];
+        generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&["-n", "3", "--format", "jsonl"], r#"{"passphrase":"chute luau hugeness","words":["chute","luau","hugeness"],"indices":[198,708,558],"wordlist":"EFF's Short Wordlist #2","entropy_bits":31.019550008653873}"#; "with default wordlist"))]
+    #[test_case(&["-w", "bip39", "--entropy-hex", "00000000000000000000000000000000", "--format", "jsonl"], r#"{"passphrase":"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about","words":["abandon","abandon","abandon","abandon","abandon","abandon","abandon","abandon","abandon","abandon","abandon","about"],"indices":[0,0,0,0,0,0,0,0,0,0,0,3],"wordlist":"BIP39 English","entropy_bits":128.0,"entropy_hex":"00000000000000000000000000000000","checksum_bits":"0011"}"#; "with BIP39 wordlist")]
+    fn writes_passphrase_as_json_line(args: &[&str], expected: &str) {
+        let cli = Cli::try_parse_from(["pgen"].iter().chain(args)).unwrap();
+        let mut output = vec![];
+        generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), format!("{expected}\n"));
+    }
+
+    #[cfg(feature = "eff-long")]
+    #[test_case("json"; "as array")]
+    #[test_case("jsonl"; "as lines")]
+    fn writes_same_passphrases_as_text_in_json(format: &str) {
+        let args = ["pgen", "-w", "eff-long", "-k", "3", "--capitalize", "title"];
+        let mut text = vec![];
+        let cli = Cli::try_parse_from(args).unwrap();
+        generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut text).unwrap();
+        let mut json = vec![];
+        let cli = Cli::try_parse_from(args.iter().chain(&["--format", format])).unwrap();
+        generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut json).unwrap();
+
+        let json = String::from_utf8(json).unwrap();
+        let outputs: Vec<serde_json::Value> = if format == "json" {
+            serde_json::from_str(&json).unwrap()
+        } else {
+            json.lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        };
+        let passphrases = outputs
+            .iter()
+            .map(|output| format!("{}\n", output["passphrase"].as_str().unwrap()))
+            .collect::<String>();
+        assert_eq!(passphrases, String::from_utf8(text).unwrap());
+    }
+
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&["--bits", "80"], "82.72"; "with default wordlist"))]
+    #[cfg_attr(feature = "eff-long", test_case(&["--bits", "128", "-w", "eff-long"], "129.25"; "with EFF long wordlist"))]
+    #[test_case(&["--bits", "80", "-w", "bip39"], "128.00"; "with BIP39 wordlist and less than 128 bits")]
+    #[test_case(&["--bits", "129", "-w", "bip39"], "160.00"; "with BIP39 wordlist and more than 128 bits")]
+    fn picks_number_of_words_for_bits(args: &[&str], expected_bits: &str) {
+        let cli = Cli::try_parse_from(["pgen", "-e"].iter().chain(args)).unwrap();
+        let mut output = vec![];
+        generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap().lines().next(),
+            Some(
+                format!("Current settings will create passphrases with {expected_bits} bits of entropy.")
+                    .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_too_many_bits_for_bip39() {
+        let cli = Cli::try_parse_from(["pgen", "--bits", "257", "-w", "bip39"]).unwrap();
+        let err = generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut vec![]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(Error::Bip39BitsUnsatisfiable(257))
+        ));
+    }
+
+    #[cfg(feature = "eff-autocomplete")]
+    #[test_case(&["--separator", "digit"], "160.62"; "with random digit separator")]
+    #[test_case(&["--separator", "none", "--capitalize", "one-random"], "127.66"; "with one random capital")]
+    #[test_case(&["--separator", "dot", "--capitalize", "title"], "124.08"; "with fixed separator and title case")]
+    fn calculates_entropy_of_transforms(args: &[&str], expected_bits: &str) {
+        let cli = Cli::try_parse_from(["pgen", "-e"].iter().chain(args)).unwrap();
+        let mut output = vec![];
+        generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap().lines().next(),
+            Some(
+                format!("Current settings will create passphrases with {expected_bits} bits of entropy.")
+                    .as_str()
+            )
+        );
+    }
+
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&["-n", "4", "--separator", "digit", "--guess-rate", "1e6"], "\
+Current settings will create passphrases with 51.33 bits of entropy.
+
+Wordlist:                EFF's Short Wordlist #2
+Words:                   4, each picked from 1296 words
+Entropy per word:        10.34 bits
+Entropy of words:        41.36 bits
+Random separators:       +9.97 bits
+Total entropy:           51.33 bits
+Possible passphrases:    2^51.33 ~= 2.82e15
+Average length:          32.27 characters
+Average time to crack:   44.70 years at 1000000 guesses per second
+"; "with default wordlist"))]
+    #[test_case(&["-w", "bip39", "-n", "15"], "\
+Current settings will create passphrases with 160.00 bits of entropy.
+
+Wordlist:                BIP39 English
+Words:                   15, each encoding 11 bits, 165 bits in total
+Entropy (ENT):           160 bits
+Checksum (CS):           5 bits, calculated from ENT, so they add no entropy
+Total entropy:           160.00 bits
+Possible passphrases:    2^160.00 ~= 1.46e48
+Average length:          95.06 characters
+Average time to crack:   2.32e36 years at 10000 guesses per second
+                         2.32e30 years at 10000000000 guesses per second
+"; "with BIP39 wordlist")]
+    fn prints_entropy_report(args: &[&str], expected: &str) {
+        let cli = Cli::try_parse_from(["pgen", "-e"].iter().chain(args)).unwrap();
+        let mut output = vec![];
+        generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test_case(0.5, "less than a second")]
+    #[test_case(59.0, "59.00 seconds")]
+    #[test_case(90.0, "1.50 minutes")]
+    #[test_case(36.0 * 60.0 * 60.0, "1.50 days")]
+    #[test_case(1e20, "3.17e12 years")]
+    fn formats_duration(seconds: f64, expected: &str) {
+        assert_eq!(format_duration(seconds), expected);
+    }
+
+    #[test_case("0"; "with zero")]
+    #[test_case("-1e6"; "with negative rate")]
+    #[test_case("inf"; "with infinite rate")]
+    #[test_case("fast"; "with text")]
+    fn rejects_invalid_guess_rate(rate: &str) {
+        assert!(Cli::try_parse_from(["pgen", "-e", "--guess-rate", rate]).is_err());
+    }
+
+    #[test_case(&["-w", "bip39", "--separator", "hyphen"]; "with BIP39 wordlist and separator")]
+    #[test_case(&["-w", "bip39", "--capitalize", "title"]; "with BIP39 wordlist and capitalization")]
+    #[test_case(&["-d", "--separator", "digit"]; "with dice and random separator")]
+    #[test_case(&["-d", "--capitalize", "one-random"]; "with dice and random capital")]
+    fn rejects_transforms_that_can_not_be_used(args: &[&str]) {
+        let cli = Cli::try_parse_from(["pgen"].iter().chain(args)).unwrap();
+        let err = generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut vec![]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(Error::NotWithBip39Wordlist(_) | Error::RandomWithDice(_))
+        ));
+    }
+
+    #[test_case(&["-w", "bip39", "--require-digit"], Error::PolicyNotWithBip39Wordlist; "with BIP39 wordlist")]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&["-d", "--min-length", "16"], Error::PolicyWithDice; "with dice"))]
+    fn rejects_policy_that_can_not_be_used(args: &[&str], expected: Error) {
+        let cli = Cli::try_parse_from(["pgen"].iter().chain(args)).unwrap();
+        let err = generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut vec![]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Error>().map(ToString::to_string),
+            Some(expected.to_string())
+        );
+    }
+
+    #[test_case(&["-w", "bip39"]; "with BIP39 wordlist")]
+    #[test_case(&["-d"]; "with dice")]
+    fn rejects_max_word_length_that_can_not_be_used(args: &[&str]) {
+        let cli =
+            Cli::try_parse_from(["pgen", "--max-word-length", "5"].iter().chain(args)).unwrap();
+        let err = generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut vec![]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(Error::NotWithBip39Wordlist(_) | Error::NotWithDice(_))
+        ));
+    }
+
+    // The 8 words that would otherwise be used only give 77.49 bits with the length limit,
+    // and 45.82 bits with the word length limit.
+    #[cfg(feature = "eff-autocomplete")]
+    #[test_case(&["--max-length", "56"], "80.72"; "with length limit")]
+    #[test_case(&["--max-word-length", "4"], "80.19"; "with word length limit")]
+    fn adds_words_to_reach_bits_with_length_limits(args: &[&str], expected_bits: &str) {
+        let cli = Cli::try_parse_from(["pgen", "-e", "--bits", "80"].iter().chain(args)).unwrap();
+        let mut output = vec![];
+        generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap().lines().next(),
+            Some(
+                format!("Current settings will create passphrases with {expected_bits} bits of entropy.")
+                    .as_str()
+            )
+        );
+    }
+
+    #[cfg(feature = "eff-autocomplete")]
+    #[test_case(&["--max-length", "30"]; "with length limit")]
+    #[test_case(&["--max-length", "60", "--max-word-length", "3"]; "with length and word length limits")]
+    fn rejects_bits_that_can_not_be_reached_with_length_limits(args: &[&str]) {
+        let cli = Cli::try_parse_from(["pgen", "-e", "--bits", "80"].iter().chain(args)).unwrap();
+        let err = generate(&cli, ChaCha20Rng::seed_from_u64(1), &mut vec![]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(Error::BitsUnsatisfiableWithinLength(80))
+        ));
+    }
+
+    #[test_case(&["--policy-file", "policy.txt", "--require-digit"]; "with policy file and policy options")]
+    #[test_case(&["--bits", "0"]; "with zero bits")]
+    #[test_case(&["--bits", "80", "-n", "6"]; "with number of words")]
+    fn rejects_invalid_bits_arguments(args: &[&str]) {
+        assert!(Cli::try_parse_from(["pgen"].iter().chain(args)).is_err());
+    }
 }
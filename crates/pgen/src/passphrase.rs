@@ -0,0 +1,1059 @@
+/*
+ * Copyright (c) 2024 Erik Nordstrøm <erik@nordstroem.no>
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+#![forbid(unsafe_code)]
+
+//! Generation of passphrases consisting of words picked at random from a wordlist.
+//!
+//! ```
+//! # #[cfg(feature = "eff-autocomplete")]
+//! # fn main() {
+//! use eff_lexical_data::WL_AUTOCOMPLETE;
+//! use pgen::passphrase::PassphraseGenerator;
+//!
+//! let mut generator = PassphraseGenerator::builder(WL_AUTOCOMPLETE)
+//!     .num_words(12)
+//!     .separator("-")
+//!     .build()
+//!     .unwrap();
+//! let passphrase = generator.generate();
+//! assert_eq!(passphrase.words().len(), 12);
+//! assert!(passphrase.entropy_bits() > 124.0);
+//! # }
+//! # #[cfg(not(feature = "eff-autocomplete"))]
+//! # fn main() {}
+//! ```
+//!
+//! Note that picking words at random from the BIP39 wordlist does not give a valid
+//! BIP39 mnemonic sentence, since the last word has to contain a checksum.
+//! Use [`crate::bip39_algorithm`] for generating BIP39 mnemonic sentences.
+
+use crate::policy::{self, Policy};
+use rand::rngs::ThreadRng;
+use rand::{thread_rng, CryptoRng, Rng, RngCore};
+use std::fmt;
+use thiserror::Error;
+
+/// Number of words used when not specified otherwise.
+pub const DEFAULT_NUM_WORDS: usize = 12;
+
+/// Separator used between words when not specified otherwise.
+pub const DEFAULT_SEPARATOR: &str = " ";
+
+/// Smallest number of words that gives at least `bits` bits of entropy,
+/// with each word adding `entropy_bits_per_word` bits.
+///
+/// Always at least one word. The number of bits per word must be positive, which it is for
+/// any wordlist of two or more words. The result is checked with the same multiplication
+/// as [`PassphraseGenerator::entropy_bits`] uses for the words, so that the two always agree.
+/// Any entropy added by random separators or capitalization comes on top of this.
+pub fn num_words_for_entropy_bits(bits: u32, entropy_bits_per_word: f64) -> usize {
+    let bits = f64::from(bits);
+    let mut num_words = ((bits / entropy_bits_per_word).ceil() as usize).max(1);
+    // Correct for any rounding errors in the division.
+    while num_words > 1 && ((num_words - 1) as f64) * entropy_bits_per_word >= bits {
+        num_words -= 1;
+    }
+    while (num_words as f64) * entropy_bits_per_word < bits {
+        num_words += 1;
+    }
+    num_words
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("Wordlist must contain at least two words")]
+    WordlistTooShort,
+    #[error("Number of words must be at least one")]
+    NoWords,
+    #[error("Fewer than two words of the wordlist are at most {0} characters long")]
+    WordLengthUnsatisfiable(usize),
+    #[error(transparent)]
+    Policy(#[from] policy::Error),
+    #[error("No passphrase of this many words from this wordlist is within the length limits of the policy")]
+    LengthUnsatisfiable,
+    #[error("Passphrases of this many words from this wordlist are too unlikely to be within the length limits of the policy")]
+    LengthUnlikely,
+}
+
+/// Passphrases are re-rolled until they are within the length limits of the policy.
+/// Settings that would need more than this many tries on average are rejected.
+const MAX_EXPECTED_TRIES: f64 = 1e6;
+
+/// Digits that are inserted into passphrases that must contain a digit.
+const DIGITS: &str = "0123456789";
+
+/// Uppercase letters that are inserted into passphrases that must contain an uppercase letter.
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Symbols that [`Separator::RandomSymbol`] picks from.
+///
+/// Chosen to be accepted by most systems, and to not need quoting in a shell.
+pub const SYMBOLS: &str = "!#%+,-./:=@^_~";
+
+/// What to put between the words of a passphrase.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Separator {
+    /// The same string between all of the words.
+    Fixed(String),
+    /// A digit (0-9) picked at random, for each place between two words.
+    RandomDigit,
+    /// A symbol picked at random from [`SYMBOLS`], for each place between two words.
+    RandomSymbol,
+}
+
+impl Separator {
+    /// Number of bits of entropy that each separator adds to a passphrase.
+    pub fn entropy_bits(&self) -> f64 {
+        match self {
+            Self::Fixed(_) => 0.0,
+            Self::RandomDigit => 10f64.log2(),
+            Self::RandomSymbol => (SYMBOLS.len() as f64).log2(),
+        }
+    }
+
+    /// Number of characters in each separator.
+    fn len(&self) -> usize {
+        match self {
+            Self::Fixed(separator) => separator.chars().count(),
+            Self::RandomDigit | Self::RandomSymbol => 1,
+        }
+    }
+
+    /// Whether every separator contains a character for which `f` is true.
+    fn always_contains(&self, f: impl Fn(char) -> bool) -> bool {
+        match self {
+            Self::Fixed(separator) => separator.chars().any(f),
+            Self::RandomDigit => DIGITS.chars().all(f),
+            Self::RandomSymbol => SYMBOLS.chars().all(f),
+        }
+    }
+
+    fn pick<R: RngCore>(&self, rng: &mut R) -> String {
+        match self {
+            Self::Fixed(separator) => separator.clone(),
+            Self::RandomDigit => rng.gen_range(0..10).to_string(),
+            Self::RandomSymbol => {
+                char::from(SYMBOLS.as_bytes()[rng.gen_range(0..SYMBOLS.len())]).to_string()
+            }
+        }
+    }
+}
+
+impl From<&str> for Separator {
+    fn from(separator: &str) -> Self {
+        Self::Fixed(separator.to_string())
+    }
+}
+
+/// How to capitalize the words of a passphrase.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Capitalization {
+    /// Words are used as they are in the wordlist.
+    #[default]
+    Unchanged,
+    /// The first letter of each word is uppercase.
+    Title,
+    /// All letters of each word are uppercase.
+    Upper,
+    /// The first letter of each word except the first word is uppercase.
+    Camel,
+    /// The first letter of one word, picked at random, is uppercase.
+    OneRandomWord,
+}
+
+impl Capitalization {
+    /// Number of bits of entropy that the capitalization adds to a passphrase of given words.
+    ///
+    /// Only [`Capitalization::OneRandomWord`] adds entropy, by picking one of the words.
+    /// If any of the words in the wordlist would be left unchanged by capitalizing it,
+    /// then which word was picked can not always be seen, so no entropy is counted for it.
+    fn entropy_bits(self, wordlist: &[&str], num_words: usize) -> f64 {
+        match self {
+            Self::OneRandomWord if wordlist.iter().all(|word| capitalize(word) != *word) => {
+                (num_words as f64).log2()
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+impl Capitalization {
+    /// Whether every passphrase of given words contains an uppercase letter.
+    fn always_has_uppercase(self, wordlist: &[&str], num_words: usize) -> bool {
+        let capitalizes_first_word = match self {
+            Self::Unchanged => false,
+            Self::Camel => num_words > 1,
+            Self::Title | Self::Upper | Self::OneRandomWord => true,
+        };
+        capitalizes_first_word
+            && wordlist
+                .iter()
+                .all(|word| word.chars().next().is_some_and(char::is_lowercase))
+    }
+}
+
+/// Make the first letter of a word uppercase.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Builder for [`PassphraseGenerator`]. Created with [`PassphraseGenerator::builder`].
+pub struct PassphraseGeneratorBuilder<'a, R> {
+    wordlist: &'a [&'a str],
+    num_words: usize,
+    separator: Separator,
+    capitalization: Capitalization,
+    max_word_length: Option<usize>,
+    policy: Policy,
+    rng: R,
+}
+
+impl<'a, R: RngCore + CryptoRng> PassphraseGeneratorBuilder<'a, R> {
+    /// Set the number of words in each passphrase. Default: [`DEFAULT_NUM_WORDS`].
+    pub fn num_words(mut self, num_words: usize) -> Self {
+        self.num_words = num_words;
+        self
+    }
+
+    /// Set the separator to put between words. Default: [`DEFAULT_SEPARATOR`].
+    ///
+    /// Either a fixed string, such as `"-"`, or a [`Separator`].
+    pub fn separator(mut self, separator: impl Into<Separator>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Set how to capitalize the words. Default: [`Capitalization::Unchanged`].
+    pub fn capitalization(mut self, capitalization: Capitalization) -> Self {
+        self.capitalization = capitalization;
+        self
+    }
+
+    /// Only pick words of at most this many characters from the wordlist. Default: no limit.
+    ///
+    /// The entropy of each word is then that of picking from the words that are short enough.
+    pub fn max_word_length(mut self, max_word_length: usize) -> Self {
+        self.max_word_length = Some(max_word_length);
+        self
+    }
+
+    /// Set the password policy that passphrases must satisfy. Default: no policy.
+    ///
+    /// For each kind of character that the policy requires, and that the passphrases would
+    /// not always contain anyway, a character of that kind, picked at random, is inserted
+    /// at a random place between two words or at either end. Passphrases that are not within
+    /// the length limits of the policy are thrown away and picked again.
+    ///
+    /// The entropy of the inserted characters is counted, but not that of the places they are
+    /// inserted at. The entropy lost by throwing away passphrases is subtracted. Lengths are
+    /// counted assuming that capitalization does not change the length of words, which holds
+    /// for all words that are ASCII.
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the random number generator to pick words with. Default: [`thread_rng`].
+    pub fn rng<R2: RngCore + CryptoRng>(self, rng: R2) -> PassphraseGeneratorBuilder<'a, R2> {
+        PassphraseGeneratorBuilder {
+            wordlist: self.wordlist,
+            num_words: self.num_words,
+            separator: self.separator,
+            capitalization: self.capitalization,
+            max_word_length: self.max_word_length,
+            policy: self.policy,
+            rng,
+        }
+    }
+
+    pub fn build(self) -> Result<PassphraseGenerator<'a, R>, Error> {
+        if self.wordlist.len() < 2 {
+            return Err(Error::WordlistTooShort);
+        }
+        if self.num_words == 0 {
+            return Err(Error::NoWords);
+        }
+        self.policy.validate()?;
+
+        let (indices, words): (Vec<_>, Vec<_>) = self
+            .wordlist
+            .iter()
+            .enumerate()
+            .filter(|(_, word)| {
+                self.max_word_length
+                    .is_none_or(|max| word.chars().count() <= max)
+            })
+            .unzip();
+        if let Some(max) = self.max_word_length.filter(|_| words.len() < 2) {
+            return Err(Error::WordLengthUnsatisfiable(max));
+        }
+
+        let has_separators = self.num_words > 1;
+        let mut insertions = vec![];
+        if self.policy.require_digit
+            && !(has_separators && self.separator.always_contains(|c| c.is_ascii_digit()))
+        {
+            insertions.push(DIGITS);
+        }
+        if self.policy.require_uppercase
+            && !self
+                .capitalization
+                .always_has_uppercase(&words, self.num_words)
+        {
+            insertions.push(UPPERCASE);
+        }
+        if self.policy.require_symbol
+            && !(has_separators && self.separator.always_contains(|c| c.is_ascii_punctuation()))
+        {
+            insertions.push(SYMBOLS);
+        }
+
+        // Passphrases are within the length limits if the total length of their words is,
+        // since the separators and inserted characters add the same length to all of them.
+        let fixed_len = (self.num_words - 1) * self.separator.len() + insertions.len();
+        let min_words_len = self
+            .policy
+            .min_length
+            .unwrap_or(0)
+            .saturating_sub(fixed_len);
+        let max_words_len = match self.policy.max_length {
+            Some(max) if max < fixed_len => return Err(Error::LengthUnsatisfiable),
+            Some(max) => max - fixed_len,
+            None => usize::MAX,
+        };
+        let length_probability =
+            words_len_probability(&words, self.num_words, min_words_len, max_words_len);
+        if length_probability == 0.0 {
+            return Err(Error::LengthUnsatisfiable);
+        }
+        if length_probability < 1.0 / MAX_EXPECTED_TRIES {
+            return Err(Error::LengthUnlikely);
+        }
+
+        Ok(PassphraseGenerator {
+            wordlist: self.wordlist,
+            words,
+            indices,
+            num_words: self.num_words,
+            separator: self.separator,
+            capitalization: self.capitalization,
+            insertions,
+            words_len_range: (min_words_len, max_words_len),
+            length_probability,
+            rng: self.rng,
+        })
+    }
+}
+
+/// Generator of passphrases, with each word picked uniformly at random from a wordlist.
+pub struct PassphraseGenerator<'a, R> {
+    wordlist: &'a [&'a str],
+    /// Words that can be picked, which are those that are not too long
+    words: Vec<&'a str>,
+    /// Indexes in the wordlist of the words that can be picked
+    indices: Vec<usize>,
+    num_words: usize,
+    separator: Separator,
+    capitalization: Capitalization,
+    /// Sets of characters to insert one character from, to satisfy the policy
+    insertions: Vec<&'static str>,
+    /// Smallest and largest total length of the words, to satisfy the policy
+    words_len_range: (usize, usize),
+    /// Probability that randomly picked words are within the length limits of the policy
+    length_probability: f64,
+    rng: R,
+}
+
+impl<'a> PassphraseGenerator<'a, ThreadRng> {
+    /// Start building a generator that picks words from given wordlist.
+    pub fn builder(wordlist: &'a [&'a str]) -> PassphraseGeneratorBuilder<'a, ThreadRng> {
+        PassphraseGeneratorBuilder {
+            wordlist,
+            num_words: DEFAULT_NUM_WORDS,
+            separator: DEFAULT_SEPARATOR.into(),
+            capitalization: Capitalization::default(),
+            max_word_length: None,
+            policy: Policy::default(),
+            rng: thread_rng(),
+        }
+    }
+}
+
+impl<'a, R: RngCore + CryptoRng> PassphraseGenerator<'a, R> {
+    /// Number of bits of entropy in each generated passphrase.
+    ///
+    /// Each word is picked independently of the others, and adds log2 of the
+    /// number of words that can be picked bits of entropy. Random separators and
+    /// random capitalization add the entropy of their random choices on top of that.
+    ///
+    /// With a policy, the entropy of inserted characters is added, and the entropy lost
+    /// by throwing away passphrases that are not within the length limits is subtracted.
+    /// The words of the passphrases that are kept are still uniformly distributed, so
+    /// the entropy of the words is the binary logarithm of the number of such passphrases.
+    pub fn entropy_bits(&self) -> f64 {
+        self.entropy_breakdown().total_bits()
+    }
+
+    /// How the entropy of each generated passphrase adds up, see [`Self::entropy_bits`].
+    pub fn entropy_breakdown(&self) -> EntropyBreakdown {
+        EntropyBreakdown {
+            num_words: self.num_words,
+            num_candidates: self.words.len(),
+            bits_per_word: (self.words.len() as f64).log2(),
+            length_limit_bits: self.length_probability.log2(),
+            separator_bits: ((self.num_words - 1) as f64) * self.separator.entropy_bits(),
+            capitalization_bits: self
+                .capitalization
+                .entropy_bits(&self.words, self.num_words),
+            insertion_bits: self
+                .insertions
+                .iter()
+                .map(|chars| (chars.len() as f64).log2())
+                .sum::<f64>(),
+        }
+    }
+
+    /// Average length in characters of generated passphrases, including separators
+    /// and any inserted characters.
+    ///
+    /// Like the length limits of the policy, this assumes that capitalization does not change
+    /// the length of words.
+    pub fn average_length(&self) -> f64 {
+        let (min_words_len, max_words_len) = self.words_len_range;
+        let fixed_len = (self.num_words - 1) * self.separator.len() + self.insertions.len();
+        // Average total length of the words of the passphrases that are within the length limits.
+        let (sum, probability) = words_len_distribution(&self.words, self.num_words)
+            .iter()
+            .enumerate()
+            .filter(|&(len, _)| (min_words_len..=max_words_len).contains(&len))
+            .fold((0.0, 0.0), |(sum, probability), (len, &p)| {
+                (sum + (len as f64) * p, probability + p)
+            });
+        fixed_len as f64 + sum / probability
+    }
+
+    /// Generate a new passphrase, satisfying the policy if any.
+    pub fn generate(&mut self) -> Passphrase<'a> {
+        let (min_words_len, max_words_len) = self.words_len_range;
+        let picks = loop {
+            let picks = (0..self.num_words)
+                .map(|_| self.rng.gen_range(0..self.words.len()))
+                .collect::<Vec<_>>();
+            let words_len = picks
+                .iter()
+                .map(|&pick| self.words[pick].chars().count())
+                .sum::<usize>();
+            if (min_words_len..=max_words_len).contains(&words_len) {
+                break picks;
+            }
+        };
+        let indices = picks.into_iter().map(|pick| self.indices[pick]).collect();
+        let mut passphrase = self.format(indices);
+        self.insert_required_chars(&mut passphrase);
+        passphrase
+    }
+
+    /// Make a passphrase of the words at given indexes in the wordlist, which have been picked
+    /// some other way, such as with dice.
+    ///
+    /// The words are separated and capitalized the same way as with [`Self::generate`].
+    /// Random separators and capitalization are picked with the random number generator.
+    /// Neither the policy nor the maximum word length is applied.
+    pub fn format(&mut self, indices: Vec<usize>) -> Passphrase<'a> {
+        let words = indices
+            .iter()
+            .map(|&idx| self.wordlist[idx])
+            .collect::<Vec<_>>();
+        let random_word = match self.capitalization {
+            Capitalization::OneRandomWord => Some(self.rng.gen_range(0..words.len())),
+            _ => None,
+        };
+        let mut text = String::new();
+        let mut word_starts = vec![];
+        for (i, &word) in words.iter().enumerate() {
+            if i > 0 {
+                text.push_str(&self.separator.pick(&mut self.rng));
+            }
+            word_starts.push(text.len());
+            let word = match self.capitalization {
+                Capitalization::Unchanged => word.to_string(),
+                Capitalization::Title => capitalize(word),
+                Capitalization::Upper => word.to_uppercase(),
+                Capitalization::Camel if i > 0 => capitalize(word),
+                Capitalization::Camel => word.to_string(),
+                Capitalization::OneRandomWord if random_word == Some(i) => capitalize(word),
+                Capitalization::OneRandomWord => word.to_string(),
+            };
+            text.push_str(&word);
+        }
+        Passphrase {
+            words,
+            indices,
+            text,
+            word_starts,
+            entropy_bits: self.entropy_bits(),
+        }
+    }
+
+    /// Insert one random character from each set of required characters, each at
+    /// a random place between two words or at either end.
+    fn insert_required_chars(&mut self, passphrase: &mut Passphrase<'a>) {
+        let mut places = passphrase.word_starts.clone();
+        places.push(passphrase.text.len());
+        let mut inserts = self
+            .insertions
+            .iter()
+            .map(|chars| {
+                let place = places[self.rng.gen_range(0..places.len())];
+                let c = chars.as_bytes()[self.rng.gen_range(0..chars.len())];
+                (place, char::from(c))
+            })
+            .collect::<Vec<_>>();
+        // Insert from the end, so that the places further in are not moved by the insertions.
+        inserts.sort_by_key(|&(place, _)| std::cmp::Reverse(place));
+        for (place, c) in inserts {
+            passphrase.text.insert(place, c);
+        }
+    }
+}
+
+/// How the entropy of passphrases from a [`PassphraseGenerator`] adds up.
+///
+/// Created with [`PassphraseGenerator::entropy_breakdown`]. All entropies are in bits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntropyBreakdown {
+    /// Number of words in each passphrase
+    pub num_words: usize,
+    /// Number of words that each word is picked from
+    pub num_candidates: usize,
+    /// Entropy that each word adds
+    pub bits_per_word: f64,
+    /// Entropy lost by picking passphrases again when they are not within the length limits
+    /// of the policy. Zero or negative.
+    pub length_limit_bits: f64,
+    /// Entropy added by random separators
+    pub separator_bits: f64,
+    /// Entropy added by random capitalization
+    pub capitalization_bits: f64,
+    /// Entropy added by characters inserted to satisfy the policy
+    pub insertion_bits: f64,
+}
+
+impl EntropyBreakdown {
+    /// Entropy of the words, before any passphrases are picked again because of length limits.
+    pub fn words_bits(&self) -> f64 {
+        (self.num_words as f64) * self.bits_per_word
+    }
+
+    /// Entropy of each passphrase, which is the sum of all of the parts.
+    pub fn total_bits(&self) -> f64 {
+        self.words_bits()
+            + self.length_limit_bits
+            + self.separator_bits
+            + self.capitalization_bits
+            + self.insertion_bits
+    }
+}
+
+/// Probability that the total length in characters of `num_words` words, picked uniformly
+/// at random from the wordlist, is within `min_len..=max_len`.
+fn words_len_probability(
+    wordlist: &[&str],
+    num_words: usize,
+    min_len: usize,
+    max_len: usize,
+) -> f64 {
+    if min_len == 0 && max_len == usize::MAX {
+        return 1.0;
+    }
+    words_len_distribution(wordlist, num_words)
+        .iter()
+        .enumerate()
+        .filter(|&(len, _)| (min_len..=max_len).contains(&len))
+        .map(|(_, &p)| p)
+        .sum::<f64>()
+        .min(1.0)
+}
+
+/// Probability of each total length in characters of `num_words` words, picked uniformly
+/// at random from the wordlist, indexed by the total length.
+fn words_len_distribution(wordlist: &[&str], num_words: usize) -> Vec<f64> {
+    // Probability of each word length.
+    let mut word_len_probabilities = vec![];
+    for word in wordlist {
+        let len = word.chars().count();
+        if word_len_probabilities.len() <= len {
+            word_len_probabilities.resize(len + 1, 0.0);
+        }
+        word_len_probabilities[len] += 1.0 / wordlist.len() as f64;
+    }
+    // Probability of each total length, adding one word at a time.
+    let mut total_len_probabilities = vec![1.0];
+    for _ in 0..num_words {
+        let mut next = vec![0.0; total_len_probabilities.len() + word_len_probabilities.len() - 1];
+        for (total_len, &p_total) in total_len_probabilities.iter().enumerate() {
+            for (word_len, &p_word) in word_len_probabilities.iter().enumerate() {
+                next[total_len + word_len] += p_total * p_word;
+            }
+        }
+        total_len_probabilities = next;
+    }
+    total_len_probabilities
+}
+
+/// A generated passphrase.
+///
+/// Formats as the words, capitalized and joined by separators.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Passphrase<'a> {
+    words: Vec<&'a str>,
+    indices: Vec<usize>,
+    text: String,
+    /// Where in the text each word starts
+    word_starts: Vec<usize>,
+    entropy_bits: f64,
+}
+
+impl<'a> Passphrase<'a> {
+    /// The words of the passphrase, in order, as they are in the wordlist.
+    pub fn words(&self) -> &[&'a str] {
+        &self.words
+    }
+
+    /// Indexes in the wordlist of the words of the passphrase, in order.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Number of bits of entropy of the passphrase.
+    pub fn entropy_bits(&self) -> f64 {
+        self.entropy_bits
+    }
+}
+
+impl fmt::Display for Passphrase<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "eff-autocomplete")]
+    use crate::passphrase::DEFAULT_NUM_WORDS;
+    use crate::passphrase::{
+        num_words_for_entropy_bits, Capitalization, Error, PassphraseGenerator,
+    };
+    #[cfg(any(feature = "eff-autocomplete", feature = "eff-long"))]
+    use crate::passphrase::{Separator, SYMBOLS};
+    use crate::policy::Policy;
+    #[cfg(feature = "eff-autocomplete")]
+    use eff_lexical_data::WL_AUTOCOMPLETE;
+    #[cfg(feature = "eff-long")]
+    use eff_lexical_data::WL_LONG;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use test_case::test_case;
+
+    #[cfg(any(feature = "eff-autocomplete", feature = "eff-long"))]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(WL_AUTOCOMPLETE, 12, 124.08; "with autocomplete wordlist and 12 words"))]
+    #[cfg_attr(feature = "eff-long", test_case(WL_LONG, 10, 129.25; "with long wordlist and 10 words"))]
+    fn calculates_entropy_bits(wordlist: &[&str], num_words: usize, expected: f64) {
+        let generator = PassphraseGenerator::builder(wordlist)
+            .num_words(num_words)
+            .build()
+            .unwrap();
+        assert_eq!(
+            format!("{:.2}", generator.entropy_bits()),
+            format!("{expected:.2}")
+        );
+    }
+
+    #[cfg(feature = "eff-long")]
+    #[test]
+    fn breaks_down_entropy_bits() {
+        let generator = PassphraseGenerator::builder(WL_LONG)
+            .num_words(6)
+            .separator(Separator::RandomDigit)
+            .capitalization(Capitalization::OneRandomWord)
+            .policy(Policy {
+                max_length: Some(48),
+                require_symbol: true,
+                ..Policy::default()
+            })
+            .build()
+            .unwrap();
+        let breakdown = generator.entropy_breakdown();
+        assert_eq!(breakdown.num_words, 6);
+        assert_eq!(breakdown.num_candidates, WL_LONG.len());
+        assert_eq!(breakdown.words_bits(), 6.0 * 7776f64.log2());
+        assert!(breakdown.length_limit_bits < 0.0);
+        assert_eq!(breakdown.separator_bits, 5.0 * 10f64.log2());
+        assert_eq!(breakdown.capitalization_bits, 6f64.log2());
+        assert_eq!(breakdown.insertion_bits, (SYMBOLS.len() as f64).log2());
+        assert_eq!(breakdown.total_bits(), generator.entropy_bits());
+    }
+
+    #[test_case(Policy::default(), 17.0; "without policy")]
+    #[test_case(Policy { require_digit: true, ..Policy::default() }, 18.0; "with inserted digit")]
+    fn calculates_average_length_with_words_of_same_length(policy: Policy, expected: f64) {
+        let generator = PassphraseGenerator::builder(&["alpha", "bravo", "delta"])
+            .num_words(3)
+            .separator("-")
+            .policy(policy)
+            .build()
+            .unwrap();
+        assert_eq!(generator.average_length(), expected);
+    }
+
+    #[cfg(feature = "eff-long")]
+    #[test]
+    fn calculates_average_length() {
+        let average_word_len =
+            WL_LONG.iter().map(|word| word.len()).sum::<usize>() as f64 / WL_LONG.len() as f64;
+        let generator = PassphraseGenerator::builder(WL_LONG)
+            .num_words(6)
+            .build()
+            .unwrap();
+        assert!((generator.average_length() - (6.0 * average_word_len + 5.0)).abs() < 1e-9);
+
+        // Passphrases that are too long are picked again, so the average is lower.
+        let limited = PassphraseGenerator::builder(WL_LONG)
+            .num_words(6)
+            .policy(Policy {
+                max_length: Some(40),
+                ..Policy::default()
+            })
+            .build()
+            .unwrap();
+        assert!(limited.average_length() < generator.average_length());
+        assert!(limited.average_length() <= 40.0);
+    }
+
+    #[cfg(feature = "eff-long")]
+    #[test]
+    fn generates_words_from_wordlist_joined_by_separator() {
+        let mut generator = PassphraseGenerator::builder(WL_LONG)
+            .num_words(6)
+            .separator("-")
+            .rng(StdRng::seed_from_u64(0))
+            .build()
+            .unwrap();
+        let passphrase = generator.generate();
+        assert_eq!(passphrase.words().len(), 6);
+        assert!(passphrase.words().iter().all(|word| WL_LONG.contains(word)));
+        assert!(passphrase
+            .indices()
+            .iter()
+            .zip(passphrase.words())
+            .all(|(&idx, word)| WL_LONG[idx] == *word));
+        assert_eq!(passphrase.to_string(), passphrase.words().join("-"));
+        assert_eq!(passphrase.entropy_bits(), generator.entropy_bits());
+    }
+
+    #[cfg(feature = "eff-autocomplete")]
+    #[test]
+    fn generates_same_passphrase_with_same_rng_seed() {
+        let mut a = PassphraseGenerator::builder(WL_AUTOCOMPLETE)
+            .rng(StdRng::seed_from_u64(42))
+            .build()
+            .unwrap();
+        let mut b = PassphraseGenerator::builder(WL_AUTOCOMPLETE)
+            .rng(StdRng::seed_from_u64(42))
+            .build()
+            .unwrap();
+        let passphrase = a.generate();
+        assert_eq!(passphrase.words().len(), DEFAULT_NUM_WORDS);
+        assert_eq!(passphrase, b.generate());
+        assert_ne!(a.generate(), passphrase);
+    }
+
+    #[cfg_attr(feature = "eff-autocomplete", test_case(WL_AUTOCOMPLETE, 80, 8; "with autocomplete wordlist and 80 bits"))]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(WL_AUTOCOMPLETE, 124, 12; "with autocomplete wordlist and 124 bits"))]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(WL_AUTOCOMPLETE, 125, 13; "with autocomplete wordlist and 125 bits"))]
+    #[cfg_attr(feature = "eff-long", test_case(WL_LONG, 128, 10; "with long wordlist and 128 bits"))]
+    #[cfg_attr(feature = "eff-long", test_case(WL_LONG, 1, 1; "with long wordlist and 1 bit"))]
+    #[test_case(bip39_lexical_data::WL_BIP39, 110, 10; "with exact number of bits per word")]
+    #[test_case(bip39_lexical_data::WL_BIP39, 111, 11; "with one bit more than exact")]
+    fn calculates_num_words_for_entropy_bits(wordlist: &[&str], bits: u32, expected: usize) {
+        let bits_per_word = (wordlist.len() as f64).log2();
+        let num_words = num_words_for_entropy_bits(bits, bits_per_word);
+        assert_eq!(num_words, expected);
+        let generator = PassphraseGenerator::builder(wordlist)
+            .num_words(num_words)
+            .build()
+            .unwrap();
+        assert!(generator.entropy_bits() >= f64::from(bits));
+    }
+
+    #[cfg(feature = "eff-long")]
+    #[test]
+    fn picks_only_words_within_max_word_length() {
+        let num_short_words = WL_LONG.iter().filter(|word| word.len() <= 4).count();
+        let mut generator = PassphraseGenerator::builder(WL_LONG)
+            .num_words(8)
+            .max_word_length(4)
+            .rng(StdRng::seed_from_u64(0))
+            .build()
+            .unwrap();
+        assert_eq!(
+            generator.entropy_bits(),
+            8.0 * (num_short_words as f64).log2()
+        );
+        for _ in 0..100 {
+            let passphrase = generator.generate();
+            assert!(passphrase.words().iter().all(|word| word.len() <= 4));
+            assert!(passphrase
+                .indices()
+                .iter()
+                .zip(passphrase.words())
+                .all(|(&idx, word)| WL_LONG[idx] == *word));
+        }
+    }
+
+    #[cfg(feature = "eff-long")]
+    #[test]
+    fn rejects_max_word_length_that_leaves_too_few_words() {
+        let result = PassphraseGenerator::builder(WL_LONG)
+            .max_word_length(2)
+            .build();
+        assert_eq!(result.err(), Some(Error::WordLengthUnsatisfiable(2)));
+    }
+
+    #[test_case(Capitalization::Unchanged, "alpha bravo charlie"; "unchanged")]
+    #[test_case(Capitalization::Title, "Alpha Bravo Charlie"; "title")]
+    #[test_case(Capitalization::Upper, "ALPHA BRAVO CHARLIE"; "upper")]
+    #[test_case(Capitalization::Camel, "alpha Bravo Charlie"; "camel")]
+    fn capitalizes_words(capitalization: Capitalization, expected: &str) {
+        let mut generator = PassphraseGenerator::builder(&["alpha", "bravo", "charlie"])
+            .capitalization(capitalization)
+            .build()
+            .unwrap();
+        let passphrase = generator.format(vec![0, 1, 2]);
+        assert_eq!(passphrase.to_string(), expected);
+        assert_eq!(passphrase.words(), ["alpha", "bravo", "charlie"]);
+        assert_eq!(passphrase.indices(), [0, 1, 2]);
+    }
+
+    #[cfg(feature = "eff-long")]
+    #[test]
+    fn capitalizes_one_random_word() {
+        let mut generator = PassphraseGenerator::builder(WL_LONG)
+            .num_words(8)
+            .separator("")
+            .capitalization(Capitalization::OneRandomWord)
+            .rng(StdRng::seed_from_u64(0))
+            .build()
+            .unwrap();
+        for _ in 0..10 {
+            let passphrase = generator.generate();
+            let text = passphrase.to_string();
+            assert_eq!(text.chars().filter(|c| c.is_uppercase()).count(), 1);
+            assert_eq!(text.to_lowercase(), passphrase.words().concat());
+        }
+    }
+
+    #[cfg(feature = "eff-autocomplete")]
+    #[test_case(Separator::RandomDigit, "0123456789"; "with random digit")]
+    #[test_case(Separator::RandomSymbol, SYMBOLS; "with random symbol")]
+    fn picks_random_separators(separator: Separator, allowed: &str) {
+        let mut generator = PassphraseGenerator::builder(WL_AUTOCOMPLETE)
+            .separator(separator)
+            .rng(StdRng::seed_from_u64(0))
+            .build()
+            .unwrap();
+        let passphrase = generator.generate();
+        let mut text = passphrase.to_string();
+        for word in passphrase.words() {
+            text = text.replacen(word, "", 1);
+        }
+        assert_eq!(text.len(), DEFAULT_NUM_WORDS - 1);
+        assert!(text.chars().all(|c| allowed.contains(c)));
+    }
+
+    #[cfg(feature = "eff-autocomplete")]
+    #[test_case(Separator::from("-"), Capitalization::Title, 124.08; "with fixed separator and title case")]
+    #[test_case(Separator::RandomDigit, Capitalization::Unchanged, 160.62; "with random digit")]
+    #[test_case(Separator::RandomSymbol, Capitalization::Upper, 165.96; "with random symbol")]
+    #[test_case(Separator::from(""), Capitalization::OneRandomWord, 127.66; "with one random capital")]
+    fn calculates_entropy_bits_of_transforms(
+        separator: Separator,
+        capitalization: Capitalization,
+        expected: f64,
+    ) {
+        let generator = PassphraseGenerator::builder(WL_AUTOCOMPLETE)
+            .separator(separator)
+            .capitalization(capitalization)
+            .build()
+            .unwrap();
+        assert_eq!(
+            format!("{:.2}", generator.entropy_bits()),
+            format!("{expected:.2}")
+        );
+    }
+
+    #[test]
+    fn counts_no_entropy_for_random_capital_that_can_not_be_seen() {
+        let generator = PassphraseGenerator::builder(&["alpha", "Bravo", "7charlie"])
+            .num_words(4)
+            .capitalization(Capitalization::OneRandomWord)
+            .build()
+            .unwrap();
+        assert_eq!(generator.entropy_bits(), 4.0 * 3f64.log2());
+    }
+
+    #[cfg(any(feature = "eff-autocomplete", feature = "eff-long"))]
+    fn legacy_policy() -> Policy {
+        Policy {
+            min_length: Some(16),
+            max_length: Some(64),
+            require_digit: true,
+            require_uppercase: true,
+            require_symbol: true,
+        }
+    }
+
+    #[cfg(any(feature = "eff-autocomplete", feature = "eff-long"))]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(WL_AUTOCOMPLETE, 5; "with autocomplete wordlist and 5 words"))]
+    #[cfg_attr(feature = "eff-long", test_case(WL_LONG, 4; "with long wordlist and 4 words"))]
+    fn satisfies_policy(wordlist: &[&str], num_words: usize) {
+        let mut generator = PassphraseGenerator::builder(wordlist)
+            .num_words(num_words)
+            .policy(legacy_policy())
+            .rng(StdRng::seed_from_u64(0))
+            .build()
+            .unwrap();
+        for _ in 0..100 {
+            let passphrase = generator.generate();
+            let text = passphrase.to_string();
+            assert!((16..=64).contains(&text.chars().count()), "{text}");
+            assert!(text.chars().any(|c| c.is_ascii_digit()), "{text}");
+            assert!(text.chars().any(|c| c.is_uppercase()), "{text}");
+            assert!(text.chars().any(|c| c.is_ascii_punctuation()), "{text}");
+            // Only the inserted characters are added to the words and separators.
+            assert_eq!(
+                text.chars().count(),
+                passphrase.words().concat().len() + (num_words - 1) + 3
+            );
+        }
+    }
+
+    #[cfg(feature = "eff-autocomplete")]
+    #[test]
+    fn does_not_insert_characters_that_are_always_there() {
+        let builder = || {
+            PassphraseGenerator::builder(WL_AUTOCOMPLETE)
+                .separator(Separator::RandomDigit)
+                .capitalization(Capitalization::Title)
+                .rng(StdRng::seed_from_u64(0))
+        };
+        let mut plain = builder().build().unwrap();
+        let mut with_policy = builder()
+            .policy(Policy {
+                require_digit: true,
+                require_uppercase: true,
+                ..Policy::default()
+            })
+            .build()
+            .unwrap();
+        assert_eq!(with_policy.entropy_bits(), plain.entropy_bits());
+        assert_eq!(with_policy.generate(), plain.generate());
+    }
+
+    #[cfg(feature = "eff-autocomplete")]
+    #[test]
+    fn calculates_entropy_bits_with_inserted_characters() {
+        let generator = PassphraseGenerator::builder(WL_AUTOCOMPLETE)
+            .policy(Policy {
+                require_digit: true,
+                require_uppercase: true,
+                require_symbol: true,
+                ..Policy::default()
+            })
+            .build()
+            .unwrap();
+        let expected = 12.0 * 1296f64.log2() + 10f64.log2() + 26f64.log2() + 14f64.log2();
+        assert_eq!(
+            format!("{:.6}", generator.entropy_bits()),
+            format!("{expected:.6}")
+        );
+    }
+
+    // With words "a" and "bb", two words without separator are 2, 3, 3 or 4 characters long.
+    #[test_case(None, Some(3), 3.0; "with maximum length")]
+    #[test_case(Some(3), None, 3.0; "with minimum length")]
+    #[test_case(Some(3), Some(3), 2.0; "with exact length")]
+    #[test_case(Some(2), Some(4), 4.0; "with all lengths")]
+    fn subtracts_entropy_of_rerolled_passphrases(
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+        num_kept: f64,
+    ) {
+        let mut generator = PassphraseGenerator::builder(&["a", "bb"])
+            .num_words(2)
+            .separator("")
+            .policy(Policy {
+                min_length,
+                max_length,
+                ..Policy::default()
+            })
+            .rng(StdRng::seed_from_u64(0))
+            .build()
+            .unwrap();
+        assert_eq!(
+            format!("{:.6}", generator.entropy_bits()),
+            format!("{:.6}", num_kept.log2())
+        );
+        for _ in 0..20 {
+            let len = generator.generate().to_string().len();
+            assert!(min_length.unwrap_or(0) <= len && len <= max_length.unwrap_or(usize::MAX));
+        }
+    }
+
+    #[cfg(any(feature = "eff-autocomplete", feature = "eff-long"))]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(WL_AUTOCOMPLETE, 12, Policy { max_length: Some(20), ..Policy::default() }, Error::LengthUnsatisfiable; "with too short maximum length"))]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(WL_AUTOCOMPLETE, 12, Policy { max_length: Some(5), ..Policy::default() }, Error::LengthUnsatisfiable; "with maximum length shorter than separators"))]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(WL_AUTOCOMPLETE, 12, Policy { min_length: Some(200), ..Policy::default() }, Error::LengthUnsatisfiable; "with too long minimum length"))]
+    #[cfg_attr(feature = "eff-long", test_case(WL_LONG, 10, Policy { min_length: Some(99), ..Policy::default() }, Error::LengthUnlikely; "with unlikely minimum length"))]
+    #[cfg_attr(feature = "eff-long", test_case(WL_LONG, 10, Policy { min_length: Some(64), max_length: Some(16), ..Policy::default() }, Error::Policy(crate::policy::Error::MinAboveMax { min: 64, max: 16 }); "with minimum above maximum"))]
+    fn rejects_policy_that_can_not_be_satisfied(
+        wordlist: &[&str],
+        num_words: usize,
+        policy: Policy,
+        expected: Error,
+    ) {
+        let result = PassphraseGenerator::builder(wordlist)
+            .num_words(num_words)
+            .policy(policy)
+            .build();
+        assert_eq!(result.err(), Some(expected));
+    }
+
+    #[test_case(&[], 12, Error::WordlistTooShort; "with empty wordlist")]
+    #[test_case(&["only"], 12, Error::WordlistTooShort; "with wordlist of one word")]
+    #[cfg_attr(feature = "eff-autocomplete", test_case(WL_AUTOCOMPLETE, 0, Error::NoWords; "with zero words"))]
+    fn rejects_invalid_settings(wordlist: &[&str], num_words: usize, expected: Error) {
+        let result = PassphraseGenerator::builder(wordlist)
+            .num_words(num_words)
+            .build();
+        assert_eq!(result.err(), Some(expected));
+    }
+}
@@ -0,0 +1,159 @@
+/*
+ * Copyright (c) 2024 Erik Nordstrøm <erik@nordstroem.no>
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+#![forbid(unsafe_code)]
+
+//! Password policies, such as "at least one digit, one uppercase letter, one symbol,
+//! and 16 to 64 characters", that generated passphrases must satisfy.
+//!
+//! A policy is enforced by [`crate::passphrase::PassphraseGenerator`], see
+//! [`crate::passphrase::PassphraseGeneratorBuilder::policy`].
+//!
+//! Policies can be read from a file with one setting per line:
+//!
+//! ```text
+//! # Policy of the legacy system
+//! min-length = 16
+//! max-length = 64
+//! require-digit = true
+//! require-uppercase = true
+//! require-symbol = true
+//! ```
+
+use thiserror::Error;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("Line {0} of policy is not of the form \"<setting> = <value>\"")]
+    InvalidLine(usize),
+    #[error("Unknown setting on line {0} of policy: {1:?}")]
+    UnknownSetting(usize, String),
+    #[error("Setting {1:?} on line {0} of policy has already been set")]
+    DuplicateSetting(usize, String),
+    #[error("Value of setting on line {0} of policy is not valid: {1:?}")]
+    InvalidValue(usize, String),
+    #[error("Minimum length {min} of policy is greater than maximum length {max}")]
+    MinAboveMax { min: usize, max: usize },
+}
+
+/// Rules that generated passphrases must satisfy.
+///
+/// Lengths are in characters, including separators and any inserted characters.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Policy {
+    /// Passphrases must be at least this many characters long
+    pub min_length: Option<usize>,
+    /// Passphrases must be at most this many characters long
+    pub max_length: Option<usize>,
+    /// Passphrases must contain at least one digit (0-9)
+    pub require_digit: bool,
+    /// Passphrases must contain at least one uppercase letter
+    pub require_uppercase: bool,
+    /// Passphrases must contain at least one symbol (ASCII punctuation)
+    pub require_symbol: bool,
+}
+
+impl Policy {
+    /// Parse a policy from text, with one `<setting> = <value>` per line.
+    ///
+    /// The settings are `min-length` and `max-length`, which take a number of characters,
+    /// and `require-digit`, `require-uppercase` and `require-symbol`, which take `true`
+    /// or `false`. Settings that are left out are not required. Empty lines and lines
+    /// starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut policy = Self::default();
+        let mut seen = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let line_num = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (setting, value) = line
+                .split_once('=')
+                .map(|(setting, value)| (setting.trim(), value.trim()))
+                .ok_or(Error::InvalidLine(line_num))?;
+            if seen.contains(&setting) {
+                return Err(Error::DuplicateSetting(line_num, setting.to_string()));
+            }
+            seen.push(setting);
+
+            let invalid_value = || Error::InvalidValue(line_num, value.to_string());
+            let length = || value.parse::<usize>().map_err(|_| invalid_value());
+            let flag = || value.parse::<bool>().map_err(|_| invalid_value());
+            match setting {
+                "min-length" => policy.min_length = Some(length()?),
+                "max-length" => policy.max_length = Some(length()?),
+                "require-digit" => policy.require_digit = flag()?,
+                "require-uppercase" => policy.require_uppercase = flag()?,
+                "require-symbol" => policy.require_symbol = flag()?,
+                _ => return Err(Error::UnknownSetting(line_num, setting.to_string())),
+            }
+        }
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Check that the policy can be satisfied at all, regardless of the wordlist.
+    pub fn validate(&self) -> Result<(), Error> {
+        match (self.min_length, self.max_length) {
+            (Some(min), Some(max)) if min > max => Err(Error::MinAboveMax { min, max }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the policy has no rules, so that every passphrase satisfies it.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::policy::{Error, Policy};
+    use test_case::test_case;
+
+    #[test]
+    fn parses_policy() {
+        let text = "# Legacy system\n\nmin-length = 16\nmax-length=64\nrequire-digit = true\n  require-uppercase = true\nrequire-symbol = false\n";
+        assert_eq!(
+            Policy::parse(text),
+            Ok(Policy {
+                min_length: Some(16),
+                max_length: Some(64),
+                require_digit: true,
+                require_uppercase: true,
+                require_symbol: false,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_empty_policy() {
+        let policy = Policy::parse("# Nothing required\n").unwrap();
+        assert!(policy.is_empty());
+    }
+
+    #[test_case("min-length 16\n", Error::InvalidLine(1); "with missing equals sign")]
+    #[test_case("\nmin-lenght = 16\n", Error::UnknownSetting(2, "min-lenght".to_string()); "with unknown setting")]
+    #[test_case("min-length = 16\nmin-length = 20\n", Error::DuplicateSetting(2, "min-length".to_string()); "with duplicate setting")]
+    #[test_case("min-length = sixteen\n", Error::InvalidValue(1, "sixteen".to_string()); "with invalid length")]
+    #[test_case("require-digit = yes\n", Error::InvalidValue(1, "yes".to_string()); "with invalid flag")]
+    #[test_case("min-length = 20\nmax-length = 16\n", Error::MinAboveMax { min: 20, max: 16 }; "with minimum above maximum")]
+    fn rejects_invalid_policy(text: &str, expected: Error) {
+        assert_eq!(Policy::parse(text), Err(expected));
+    }
+}
@@ -0,0 +1,514 @@
+/*
+ * Copyright (c) 2024 Erik Nordstrøm <erik@nordstroem.no>
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+#![forbid(unsafe_code)]
+
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A wordlist, along with metadata about it.
+///
+/// Implemented for the wordlists of both `eff-lexical-data` and `bip39-lexical-data`,
+/// so that they can be used interchangeably.
+pub trait Wordlist {
+    /// Name of the wordlist
+    fn name(&self) -> &str;
+    /// The words of the wordlist, in order
+    fn words(&self) -> &[&str];
+    /// Number of six-sided dice to throw to pick a word, for wordlists made for use with dice
+    fn dice_digits(&self) -> Option<u32>;
+    /// Where the wordlist data comes from
+    fn source_url(&self) -> Option<&str>;
+    /// Smallest number of leading characters that uniquely identifies each word
+    fn unique_prefix_len(&self) -> usize;
+    /// Smallest edit distance (Levenshtein distance) between any two words
+    fn min_edit_distance(&self) -> usize;
+    /// Number of words to use for a passphrase, unless otherwise specified
+    fn default_num_words(&self) -> usize;
+
+    /// Number of bits of entropy added by each word picked uniformly at random from the wordlist.
+    fn entropy_bits_per_word(&self) -> f64 {
+        (self.words().len() as f64).log2()
+    }
+}
+
+macro_rules! impl_wordlist {
+    ($t:ty) => {
+        impl Wordlist for $t {
+            fn name(&self) -> &str {
+                self.name
+            }
+            fn words(&self) -> &[&str] {
+                self.words
+            }
+            fn dice_digits(&self) -> Option<u32> {
+                self.dice_digits
+            }
+            fn source_url(&self) -> Option<&str> {
+                Some(self.source_url)
+            }
+            fn unique_prefix_len(&self) -> usize {
+                self.unique_prefix_len
+            }
+            fn min_edit_distance(&self) -> usize {
+                self.min_edit_distance
+            }
+            fn default_num_words(&self) -> usize {
+                self.default_num_words
+            }
+        }
+    };
+}
+
+impl_wordlist!(eff_lexical_data::Wordlist);
+impl_wordlist!(bip39_lexical_data::Wordlist);
+
+/// Passphrases made with the default number of words of a loaded wordlist have at least this
+/// many bits of entropy. This gives the same number of words as for the built-in EFF wordlists.
+const MIN_DEFAULT_ENTROPY_BITS: f64 = 120.0;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("Line {0} of wordlist is empty")]
+    EmptyLine(usize),
+    #[error("Word on line {0} of wordlist contains whitespace: {1:?}")]
+    Whitespace(usize, String),
+    #[error("Word on line {0} of wordlist contains non-ASCII characters: {1:?}")]
+    NonAscii(usize, String),
+    #[error(
+        "Word on line {line} of wordlist is a duplicate of the word on line {first_line}: {word:?}"
+    )]
+    Duplicate {
+        line: usize,
+        first_line: usize,
+        word: String,
+    },
+    #[error(
+        "Line {0} of wordlist is not of the form \"<dice digits><tab><word>\" like the other lines"
+    )]
+    MixedFormat(usize),
+    #[error("Dice digits on line {0} of wordlist are not valid: {1:?}")]
+    InvalidDiceDigits(usize, String),
+    #[error("Wordlist with {dice_digits} dice digits must contain {expected} words, but contains {actual}")]
+    IncompleteDiceWordlist {
+        dice_digits: u32,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Wordlist must contain at least two words, but contains {0}")]
+    TooFewWords(usize),
+}
+
+/// A wordlist loaded at runtime, for example from a file.
+///
+/// Borrows the words from the text it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedWordlist<'a> {
+    name: String,
+    words: Vec<&'a str>,
+    dice_digits: Option<u32>,
+}
+
+impl<'a> LoadedWordlist<'a> {
+    /// Parse a wordlist from text.
+    ///
+    /// Two formats are supported, the same as for the built-in wordlists:
+    ///
+    /// - One word per line, like the BIP39 wordlists.
+    /// - One word per line, preceded by its dice digits and a tab, like the EFF wordlists
+    ///   (`11111\tabacus`). The dice digits must be numbered in order, starting from all ones,
+    ///   and cover every possible throw of the dice. Words can then be picked with dice.
+    ///
+    /// Words must be unique, non-empty, ASCII, and must not contain whitespace.
+    pub fn parse(name: &str, text: &'a str) -> Result<Self, Error> {
+        let is_eff_format = text.lines().next().is_some_and(|line| line.contains('\t'));
+
+        let mut words = vec![];
+        let mut first_lines = HashMap::new();
+        let mut dice_digits = None;
+        for (i, line) in text.lines().enumerate() {
+            let line_num = i + 1;
+            let word = if is_eff_format {
+                let Some((digits, word)) = line.split_once('\t') else {
+                    return Err(Error::MixedFormat(line_num));
+                };
+                let num_digits = digits.len() as u32;
+                if *dice_digits.get_or_insert(num_digits) != num_digits
+                    || dice_index(digits) != Some(words.len())
+                {
+                    return Err(Error::InvalidDiceDigits(line_num, digits.to_string()));
+                }
+                word
+            } else {
+                line
+            };
+
+            if word.is_empty() {
+                return Err(Error::EmptyLine(line_num));
+            }
+            if word.contains(char::is_whitespace) {
+                return Err(Error::Whitespace(line_num, word.to_string()));
+            }
+            if !word.is_ascii() {
+                return Err(Error::NonAscii(line_num, word.to_string()));
+            }
+            if let Some(&first_line) = first_lines.get(word) {
+                return Err(Error::Duplicate {
+                    line: line_num,
+                    first_line,
+                    word: word.to_string(),
+                });
+            }
+            first_lines.insert(word, line_num);
+            words.push(word);
+        }
+
+        if words.len() < 2 {
+            return Err(Error::TooFewWords(words.len()));
+        }
+        if let Some(dice_digits) = dice_digits {
+            // Every possible throw of the dice must give a word.
+            if 6usize.checked_pow(dice_digits) != Some(words.len()) {
+                return Err(Error::IncompleteDiceWordlist {
+                    dice_digits,
+                    expected: 6usize.saturating_pow(dice_digits),
+                    actual: words.len(),
+                });
+            }
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            words,
+            dice_digits,
+        })
+    }
+}
+
+impl Wordlist for LoadedWordlist<'_> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn words(&self) -> &[&str] {
+        &self.words
+    }
+    fn dice_digits(&self) -> Option<u32> {
+        self.dice_digits
+    }
+    fn source_url(&self) -> Option<&str> {
+        None
+    }
+    fn unique_prefix_len(&self) -> usize {
+        unique_prefix_len(&self.words)
+    }
+    fn min_edit_distance(&self) -> usize {
+        min_edit_distance(&self.words)
+    }
+    fn default_num_words(&self) -> usize {
+        (MIN_DEFAULT_ENTROPY_BITS / self.entropy_bits_per_word()).ceil() as usize
+    }
+}
+
+/// Properties of a wordlist that affect the quality of passphrases made from it.
+///
+/// Created with [`analyze`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis<'a> {
+    /// Number of words, including any duplicates
+    pub num_words: usize,
+    /// Number of bits of entropy added by each word picked uniformly at random from the wordlist.
+    ///
+    /// Duplicate words are more likely to be picked than other words, and reduce the entropy.
+    pub entropy_bits_per_word: f64,
+    /// Words that occur more than once, in the order that their second occurrence appears
+    pub duplicates: Vec<&'a str>,
+    /// Length in characters of the shortest word
+    pub shortest_word_len: usize,
+    /// Length in characters of the longest word
+    pub longest_word_len: usize,
+    /// Average length in characters of the words
+    pub average_word_len: f64,
+    /// Smallest number of leading characters that uniquely identifies each (distinct) word
+    pub unique_prefix_len: usize,
+    /// Smallest edit distance (Levenshtein distance) between any two distinct words
+    pub min_edit_distance: usize,
+    /// Pairs of words where the first word is a prefix of the second word.
+    ///
+    /// Passphrases made from such words can be ambiguous when the words are not separated,
+    /// for example "sun" + "rise" + "n" could be confused with "sunrise" + "n".
+    pub prefix_words: Vec<(&'a str, &'a str)>,
+}
+
+/// Analyze the quality of a wordlist.
+pub fn analyze<'a>(words: &[&'a str]) -> Analysis<'a> {
+    let mut counts = HashMap::new();
+    let mut distinct = vec![];
+    let mut duplicates = vec![];
+    for &word in words {
+        let count = counts.entry(word).or_insert(0usize);
+        *count += 1;
+        match count {
+            1 => distinct.push(word),
+            2 => duplicates.push(word),
+            _ => {}
+        }
+    }
+
+    let n = words.len() as f64;
+    // Shannon entropy of picking a word at random, which is log2(n) when there are no duplicates.
+    let entropy_bits_per_word = if words.is_empty() {
+        0.0
+    } else {
+        n.log2()
+            - counts
+                .values()
+                .map(|&count| count as f64 * (count as f64).log2())
+                .sum::<f64>()
+                / n
+    };
+
+    let lens = words.iter().map(|w| w.chars().count());
+
+    let mut sorted = distinct.clone();
+    sorted.sort_unstable();
+    let mut prefix_words = vec![];
+    for (i, prefix) in sorted.iter().enumerate() {
+        // In sorted order, all words that start with a given word come right after it.
+        prefix_words.extend(
+            sorted[i + 1..]
+                .iter()
+                .take_while(|word| word.starts_with(prefix))
+                .map(|&word| (*prefix, word)),
+        );
+    }
+
+    Analysis {
+        num_words: words.len(),
+        entropy_bits_per_word,
+        duplicates,
+        shortest_word_len: lens.clone().min().unwrap_or(0),
+        longest_word_len: lens.clone().max().unwrap_or(0),
+        average_word_len: lens.sum::<usize>() as f64 / n,
+        unique_prefix_len: unique_prefix_len(&distinct),
+        min_edit_distance: min_edit_distance(&distinct),
+        prefix_words,
+    }
+}
+
+/// Get the words of a wordlist in either of the formats supported by [`LoadedWordlist::parse`],
+/// without validating them. Used for analyzing wordlists that might not be valid.
+pub fn words_unchecked(text: &str) -> Vec<&str> {
+    text.lines()
+        .map(|line| line.split_once('\t').map_or(line, |(_, word)| word))
+        .collect()
+}
+
+/// Get the zero-based index of the word with given dice digits, such as `11111` for 0.
+///
+/// Returns `None` if the digits are not valid, or if there are too many of them for the index
+/// to fit in an `usize`.
+fn dice_index(digits: &str) -> Option<usize> {
+    digits.chars().try_fold(0usize, |index, c| match c {
+        '1'..='6' => index.checked_mul(6)?.checked_add(c as usize - '1' as usize),
+        _ => None,
+    })
+}
+
+// `unique_prefix_len` and `min_edit_distance`, shared with the build scripts of the lexical data crates.
+include!("../wordlist_metrics.rs");
+
+#[cfg(test)]
+mod test {
+    use crate::wordlist::{
+        analyze, edit_distance, words_unchecked, Error, LoadedWordlist, Wordlist,
+    };
+    use bip39_lexical_data::WORDLIST_BIP39;
+    #[cfg(feature = "bip39-cs")]
+    use bip39_lexical_data::WORDLIST_BIP39_CS;
+    #[cfg(feature = "bip39-es")]
+    use bip39_lexical_data::WORDLIST_BIP39_ES;
+    #[cfg(feature = "bip39-fr")]
+    use bip39_lexical_data::WORDLIST_BIP39_FR;
+    #[cfg(feature = "bip39-it")]
+    use bip39_lexical_data::WORDLIST_BIP39_IT;
+    #[cfg(feature = "bip39-ja")]
+    use bip39_lexical_data::WORDLIST_BIP39_JA;
+    #[cfg(feature = "bip39-ko")]
+    use bip39_lexical_data::WORDLIST_BIP39_KO;
+    #[cfg(feature = "bip39-pt")]
+    use bip39_lexical_data::WORDLIST_BIP39_PT;
+    #[cfg(feature = "bip39-zh-hans")]
+    use bip39_lexical_data::WORDLIST_BIP39_ZH_HANS;
+    #[cfg(feature = "bip39-zh-hant")]
+    use bip39_lexical_data::WORDLIST_BIP39_ZH_HANT;
+    #[cfg(feature = "eff-autocomplete")]
+    use eff_lexical_data::WORDLIST_AUTOCOMPLETE;
+    #[cfg(feature = "eff-long")]
+    use eff_lexical_data::WORDLIST_LONG;
+    #[cfg(feature = "eff-short")]
+    use eff_lexical_data::WORDLIST_SHORT;
+    use test_case::test_case;
+
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&WORDLIST_AUTOCOMPLETE, 1296, Some(4), 12; "with EFF autocomplete wordlist"))]
+    #[cfg_attr(feature = "eff-long", test_case(&WORDLIST_LONG, 7776, Some(5), 10; "with EFF long wordlist"))]
+    #[cfg_attr(feature = "eff-short", test_case(&WORDLIST_SHORT, 1296, Some(4), 12; "with EFF short wordlist"))]
+    #[test_case(&WORDLIST_BIP39, 2048, None, 12; "with BIP39 English wordlist")]
+    fn has_expected_metadata(
+        wordlist: &dyn Wordlist,
+        len: usize,
+        dice_digits: Option<u32>,
+        default_num_words: usize,
+    ) {
+        assert_eq!(wordlist.words().len(), len);
+        assert_eq!(wordlist.dice_digits(), dice_digits);
+        assert_eq!(wordlist.default_num_words(), default_num_words);
+        // Wordlists made for dice have exactly as many words as there are possible dice throws.
+        if let Some(dice_digits) = dice_digits {
+            assert_eq!(wordlist.words().len(), 6usize.pow(dice_digits));
+        }
+    }
+
+    // The properties that EFF's Short Wordlist #2 was designed to have,
+    // see <https://www.eff.org/deeplinks/2016/07/new-wordlists-random-passphrases>.
+    #[cfg(feature = "eff-autocomplete")]
+    #[test]
+    fn autocomplete_wordlist_has_unique_three_character_prefixes_and_edit_distance_three() {
+        assert_eq!(WORDLIST_AUTOCOMPLETE.unique_prefix_len(), 3);
+        assert_eq!(WORDLIST_AUTOCOMPLETE.min_edit_distance(), 3);
+    }
+
+    // BIP39 words are uniquely identified by their first four letters.
+    #[test]
+    fn bip39_english_wordlist_has_unique_four_character_prefixes() {
+        assert_eq!(WORDLIST_BIP39.unique_prefix_len(), 4);
+    }
+
+    #[test]
+    fn parses_simple_wordlist() {
+        let wordlist = LoadedWordlist::parse("test", "apple\nbanana\ncherry\n").unwrap();
+        assert_eq!(wordlist.name(), "test");
+        assert_eq!(wordlist.words(), &["apple", "banana", "cherry"]);
+        assert_eq!(wordlist.dice_digits(), None);
+        assert_eq!(wordlist.entropy_bits_per_word(), 3f64.log2());
+    }
+
+    #[test]
+    fn parses_eff_wordlist() {
+        let text = (0..36)
+            .map(|i| format!("{}{}\tword{i}\n", i / 6 + 1, i % 6 + 1))
+            .collect::<String>();
+        let wordlist = LoadedWordlist::parse("test", &text).unwrap();
+        assert_eq!(wordlist.words().len(), 36);
+        assert_eq!(wordlist.words()[7], "word7");
+        assert_eq!(wordlist.dice_digits(), Some(2));
+    }
+
+    #[cfg(feature = "eff-long")]
+    #[test]
+    fn parses_same_as_built_in_eff_wordlist() {
+        let text = include_str!("../../eff-lexical-data/data/eff_large_wordlist.txt");
+        let wordlist = LoadedWordlist::parse("test", text).unwrap();
+        assert_eq!(wordlist.words(), eff_lexical_data::WL_LONG);
+        assert_eq!(wordlist.dice_digits(), Some(5));
+        assert_eq!(
+            wordlist.default_num_words(),
+            WORDLIST_LONG.default_num_words()
+        );
+    }
+
+    #[test_case("apple\n\nbanana", Error::EmptyLine(2); "with empty line")]
+    #[test_case("apple\nbanana \ncherry", Error::Whitespace(2, "banana ".into()); "with trailing whitespace")]
+    #[test_case("apple\nice cream", Error::Whitespace(2, "ice cream".into()); "with whitespace in word")]
+    #[test_case("apple\ncrème", Error::NonAscii(2, "crème".into()); "with non-ASCII word")]
+    #[test_case("apple\nbanana\napple", Error::Duplicate { line: 3, first_line: 1, word: "apple".into() }; "with duplicate word")]
+    #[test_case("apple", Error::TooFewWords(1); "with single word")]
+    #[test_case("", Error::TooFewWords(0); "with no words")]
+    #[test_case("1\tapple\nbanana", Error::MixedFormat(2); "with mixed formats")]
+    #[test_case("1\tapple\n3\tbanana", Error::InvalidDiceDigits(2, "3".into()); "with dice digits out of order")]
+    #[test_case("1\tapple\n2\tbanana", Error::IncompleteDiceWordlist { dice_digits: 1, expected: 6, actual: 2 }; "with too few words for dice")]
+    #[test_case("6666666666666666666666666666\tapple\n1\tbanana", Error::InvalidDiceDigits(1, "6666666666666666666666666666".into()); "with dice digits too many to index")]
+    fn rejects_invalid_wordlist(text: &str, expected: Error) {
+        assert_eq!(LoadedWordlist::parse("test", text), Err(expected));
+    }
+
+    #[test_case("kitten", "sitting", 3)]
+    #[test_case("flaw", "lawn", 2)]
+    #[test_case("abc", "abc", 0)]
+    #[test_case("", "abc", 3)]
+    fn calculates_edit_distance(a: &str, b: &str, expected: usize) {
+        let a = a.chars().collect::<Vec<_>>();
+        let b = b.chars().collect::<Vec<_>>();
+        let mut rows = [vec![], vec![]];
+        assert_eq!(edit_distance(&a, &b, usize::MAX, &mut rows), expected);
+        // Distances that are not less than the limit are reported as the limit.
+        assert_eq!(edit_distance(&a, &b, 1, &mut rows), expected.min(1));
+    }
+
+    // The metadata generated at build time should agree with the analysis done at runtime.
+    #[cfg_attr(feature = "eff-autocomplete", test_case(&WORDLIST_AUTOCOMPLETE; "with EFF autocomplete wordlist"))]
+    #[cfg_attr(feature = "eff-long", test_case(&WORDLIST_LONG; "with EFF long wordlist"))]
+    #[cfg_attr(feature = "eff-short", test_case(&WORDLIST_SHORT; "with EFF short wordlist"))]
+    #[test_case(&WORDLIST_BIP39; "with BIP39 English wordlist")]
+    #[cfg_attr(feature = "bip39-cs", test_case(&WORDLIST_BIP39_CS; "with BIP39 Czech wordlist"))]
+    #[cfg_attr(feature = "bip39-es", test_case(&WORDLIST_BIP39_ES; "with BIP39 Spanish wordlist"))]
+    #[cfg_attr(feature = "bip39-fr", test_case(&WORDLIST_BIP39_FR; "with BIP39 French wordlist"))]
+    #[cfg_attr(feature = "bip39-it", test_case(&WORDLIST_BIP39_IT; "with BIP39 Italian wordlist"))]
+    #[cfg_attr(feature = "bip39-ja", test_case(&WORDLIST_BIP39_JA; "with BIP39 Japanese wordlist"))]
+    #[cfg_attr(feature = "bip39-ko", test_case(&WORDLIST_BIP39_KO; "with BIP39 Korean wordlist"))]
+    #[cfg_attr(feature = "bip39-pt", test_case(&WORDLIST_BIP39_PT; "with BIP39 Portuguese wordlist"))]
+    #[cfg_attr(feature = "bip39-zh-hans", test_case(&WORDLIST_BIP39_ZH_HANS; "with BIP39 Chinese, Simplified wordlist"))]
+    #[cfg_attr(feature = "bip39-zh-hant", test_case(&WORDLIST_BIP39_ZH_HANT; "with BIP39 Chinese, Traditional wordlist"))]
+    fn analysis_matches_built_in_metadata(wordlist: &dyn Wordlist) {
+        let analysis = analyze(wordlist.words());
+        assert_eq!(analysis.num_words, wordlist.words().len());
+        assert_eq!(
+            analysis.entropy_bits_per_word,
+            wordlist.entropy_bits_per_word()
+        );
+        assert!(analysis.duplicates.is_empty());
+        assert_eq!(analysis.unique_prefix_len, wordlist.unique_prefix_len());
+        assert_eq!(analysis.min_edit_distance, wordlist.min_edit_distance());
+    }
+
+    // Since every word of EFF's Short Wordlist #2 has a unique three-character prefix,
+    // no word can be a prefix of another word.
+    #[cfg(feature = "eff-autocomplete")]
+    #[test]
+    fn autocomplete_wordlist_has_no_words_that_are_prefixes_of_other_words() {
+        assert!(analyze(WORDLIST_AUTOCOMPLETE.words).prefix_words.is_empty());
+    }
+
+    #[test]
+    fn analyzes_wordlist_with_problems() {
+        let words = words_unchecked("1\tsun\n2\tsunrise\n3\trise\n4\tsun\n5\tsunny\n6\tmoon\n");
+        let analysis = analyze(&words);
+        assert_eq!(analysis.num_words, 6);
+        assert_eq!(analysis.duplicates, vec!["sun"]);
+        assert_eq!(analysis.shortest_word_len, 3);
+        assert_eq!(analysis.longest_word_len, 7);
+        assert_eq!(analysis.average_word_len, 26.0 / 6.0);
+        assert_eq!(analysis.min_edit_distance, 2);
+        assert_eq!(
+            analysis.prefix_words,
+            vec![("sun", "sunny"), ("sun", "sunrise")]
+        );
+        // "sun" is picked with probability 2/6, and each of the other four words with 1/6.
+        let expected =
+            -(2.0 / 6.0) * (2.0f64 / 6.0).log2() - 4.0 * (1.0 / 6.0) * (1.0f64 / 6.0).log2();
+        assert!((analysis.entropy_bits_per_word - expected).abs() < 1e-9);
+        assert!(analysis.entropy_bits_per_word < 6f64.log2());
+    }
+}
@@ -0,0 +1,91 @@
+/*
+ * Copyright (c) 2024 Erik Nordstrøm <erik@nordstroem.no>
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ */
+
+// Wordlist metrics, shared with `include!` between `src/wordlist.rs` and the build scripts
+// of the lexical data crates, which compute the metrics of their wordlists at build time.
+// The lexical data crates have a symlink to this file, so that each crate can be packaged
+// on its own. Only items are allowed here, and paths are written out in full, since the
+// file is included into modules with different imports.
+
+/// Smallest number of leading characters that uniquely identifies each word.
+pub fn unique_prefix_len<S: AsRef<str>>(words: &[S]) -> usize {
+    let max_len = words
+        .iter()
+        .map(|w| w.as_ref().chars().count())
+        .max()
+        .unwrap_or(0);
+    (1..=max_len)
+        .find(|&n| {
+            let mut prefixes = std::collections::HashSet::new();
+            words
+                .iter()
+                .all(|w| prefixes.insert(w.as_ref().chars().take(n).collect::<String>()))
+        })
+        .unwrap_or(max_len)
+}
+
+/// Smallest edit distance (Levenshtein distance) between any two words.
+///
+/// Returns `usize::MAX` if there are fewer than two words.
+pub fn min_edit_distance<S: AsRef<str>>(words: &[S]) -> usize {
+    let words = words
+        .iter()
+        .map(|w| w.as_ref().chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut rows = [vec![], vec![]];
+    let mut min = usize::MAX;
+    for (i, a) in words.iter().enumerate() {
+        for b in &words[i + 1..] {
+            // The edit distance is at least the difference in length, so we can skip those
+            // pairs that could not possibly be closer than the closest pair found so far.
+            if a.len().abs_diff(b.len()) >= min {
+                continue;
+            }
+            min = edit_distance(a, b, min, &mut rows);
+            // Words are unique, so no two words can be closer than this.
+            if min == 1 {
+                return min;
+            }
+        }
+    }
+    min
+}
+
+/// Levenshtein distance between two words, or `limit` if it is not less than `limit`.
+///
+/// The `rows` buffers are reused between calls to avoid allocating for every pair of words.
+fn edit_distance(a: &[char], b: &[char], limit: usize, rows: &mut [Vec<usize>; 2]) -> usize {
+    let [prev, curr] = rows;
+    prev.clear();
+    prev.extend(0..=b.len());
+    curr.clear();
+    curr.resize(b.len() + 1, 0);
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        let mut row_min = curr[0];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+            row_min = row_min.min(curr[j + 1]);
+        }
+        // Values in later rows are never less than the smallest value of the current row.
+        if row_min >= limit {
+            return limit;
+        }
+        std::mem::swap(prev, curr);
+    }
+    prev[b.len()].min(limit)
+}